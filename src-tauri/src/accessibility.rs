@@ -0,0 +1,89 @@
+use serde::Serialize;
+
+use crate::config::AccessibilityConfig;
+
+#[cfg(target_os = "windows")]
+use windows::{
+    Win32::Foundation::BOOL,
+    Win32::Graphics::Dwm::DwmGetColorizationColor,
+    Win32::UI::Accessibility::{HCF_HIGHCONTRASTON, HIGHCONTRASTW},
+    Win32::UI::WindowsAndMessaging::{
+        SPI_GETCLIENTAREAANIMATION, SPI_GETHIGHCONTRAST, SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS,
+        SystemParametersInfoW,
+    },
+};
+
+#[derive(Debug, Serialize, Clone, Copy)]
+pub struct AccessibilityModes {
+    pub reduced_motion: bool,
+    pub high_contrast: bool,
+}
+
+/// Effective modes: explicit config overrides win, otherwise follow Windows.
+/// Queried on every call so toggling the system setting is picked up live.
+pub fn resolve(overrides: &AccessibilityConfig) -> AccessibilityModes {
+    AccessibilityModes {
+        reduced_motion: overrides
+            .reduced_motion
+            .unwrap_or_else(system_reduced_motion),
+        high_contrast: overrides.high_contrast.unwrap_or_else(system_high_contrast),
+    }
+}
+
+/// "Show animations in Windows" off => reduced motion.
+fn system_reduced_motion() -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        let mut animations = BOOL(1);
+        unsafe {
+            if SystemParametersInfoW(
+                SPI_GETCLIENTAREAANIMATION,
+                0,
+                Some(&mut animations as *mut BOOL as *mut _),
+                SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+            )
+            .is_ok()
+            {
+                return !animations.as_bool();
+            }
+        }
+    }
+    false
+}
+
+fn system_high_contrast() -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        let mut hc = HIGHCONTRASTW {
+            cbSize: std::mem::size_of::<HIGHCONTRASTW>() as u32,
+            ..Default::default()
+        };
+        unsafe {
+            if SystemParametersInfoW(
+                SPI_GETHIGHCONTRAST,
+                hc.cbSize,
+                Some(&mut hc as *mut HIGHCONTRASTW as *mut _),
+                SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+            )
+            .is_ok()
+            {
+                return hc.dwFlags.0 & HCF_HIGHCONTRASTON.0 != 0;
+            }
+        }
+    }
+    false
+}
+
+/// The Windows accent (DWM colorization) colour as `#rrggbb`, ignoring its
+/// alpha.
+pub fn system_accent_color() -> Option<String> {
+    #[cfg(target_os = "windows")]
+    {
+        let mut argb = 0u32;
+        let mut opaque = BOOL(0);
+        unsafe { DwmGetColorizationColor(&mut argb, &mut opaque) }.ok()?;
+        return Some(format!("#{:06x}", argb & 0x00ff_ffff));
+    }
+    #[cfg(not(target_os = "windows"))]
+    None
+}
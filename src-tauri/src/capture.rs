@@ -0,0 +1,77 @@
+use image::RgbaImage;
+use windows::Win32::Foundation::{HWND, RECT};
+use windows::Win32::Graphics::Gdi::{
+    BI_RGB, BITMAPINFO, BITMAPINFOHEADER, CreateCompatibleDC, CreateDIBSection, DIB_RGB_COLORS,
+    DeleteDC, DeleteObject, GetDC, ReleaseDC, SelectObject,
+};
+use windows::Win32::Storage::Xps::{PRINT_WINDOW_FLAGS, PrintWindow};
+use windows::Win32::UI::WindowsAndMessaging::{GetClientRect, PW_RENDERFULLCONTENT};
+
+/// Render `hwnd`'s client area, including its GPU-composed WebView content,
+/// into an image. Alpha is kept where the window reports it.
+pub fn capture_window(hwnd: HWND) -> Result<RgbaImage, String> {
+    unsafe {
+        let mut rect = RECT::default();
+        GetClientRect(hwnd, &mut rect).map_err(|e| e.to_string())?;
+        let width = (rect.right - rect.left).max(0) as u32;
+        let height = (rect.bottom - rect.top).max(0) as u32;
+        if width == 0 || height == 0 {
+            return Err("Window has no visible area".to_string());
+        }
+
+        let screen_dc = GetDC(HWND::default());
+        let dc = CreateCompatibleDC(screen_dc);
+
+        // 32-bit top-down DIB, so rows come out in image order
+        let bmi = BITMAPINFO {
+            bmiHeader: BITMAPINFOHEADER {
+                biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                biWidth: width as i32,
+                biHeight: -(height as i32),
+                biPlanes: 1,
+                biBitCount: 32,
+                biCompression: BI_RGB.0,
+                ..Default::default()
+            },
+            bmiColors: [Default::default()],
+        };
+        let mut bits: *mut std::ffi::c_void = std::ptr::null_mut();
+        let captured = match CreateDIBSection(dc, &bmi, DIB_RGB_COLORS, &mut bits, None, 0) {
+            Ok(bitmap) => {
+                let old = SelectObject(dc, bitmap);
+                let printed =
+                    PrintWindow(hwnd, dc, PRINT_WINDOW_FLAGS(PW_RENDERFULLCONTENT)).as_bool();
+                let pixels =
+                    std::slice::from_raw_parts(bits as *const u8, (width * height * 4) as usize)
+                        .to_vec();
+                SelectObject(dc, old);
+                DeleteObject(bitmap);
+                if printed {
+                    Ok(pixels)
+                } else {
+                    Err("PrintWindow failed".to_string())
+                }
+            }
+            Err(e) => Err(e.to_string()),
+        };
+
+        DeleteDC(dc);
+        ReleaseDC(HWND::default(), screen_dc);
+
+        let mut pixels = captured?;
+        // BGRA → RGBA
+        for px in pixels.chunks_exact_mut(4) {
+            px.swap(0, 2);
+        }
+        // All-zero alpha means GDI didn't fill the channel in, not that the
+        // window is invisible
+        if pixels.chunks_exact(4).all(|px| px[3] == 0) {
+            for px in pixels.chunks_exact_mut(4) {
+                px[3] = 255;
+            }
+        }
+
+        RgbaImage::from_raw(width, height, pixels)
+            .ok_or_else(|| "Captured pixels don't match the window size".to_string())
+    }
+}
@@ -0,0 +1,70 @@
+use crate::AppState;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::Manager;
+
+/// How often the cursor is checked against the bar. Short, so a click right
+/// after moving onto the bar still lands on it.
+const POLL_INTERVAL: Duration = Duration::from_millis(30);
+
+/// How often the settings are rechecked while there's nothing to track:
+/// click-through off, the dock hidden, or minimal mode.
+const IDLE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// The bar's bounds within the dock window, in logical (CSS) pixels.
+#[derive(Debug, Clone, Copy, serde::Deserialize)]
+pub struct BarRect {
+    pub left: f64,
+    pub top: f64,
+    pub right: f64,
+    pub bottom: f64,
+}
+
+/// Let clicks on the transparent part of the dock window (beside and above
+/// the bar) through to whatever is underneath. The window ignores the mouse
+/// while the cursor is off the bar and takes it back once it's on it.
+///
+/// Minimal mode skips the fast poll to save battery, so the whole window
+/// takes clicks there.
+pub fn watch(window: tauri::WebviewWindow) {
+    std::thread::spawn(move || {
+        let mut ignoring = false;
+        let mut interval = POLL_INTERVAL;
+        loop {
+            std::thread::sleep(interval);
+
+            let (active, bar) = window
+                .state::<Mutex<AppState>>()
+                .lock()
+                .map(|s| {
+                    // The auto-hidden sliver must stay hoverable to reveal the dock
+                    let active = s.config.click_through && !s.is_hidden && !s.minimal;
+                    (active, s.bar_rect)
+                })
+                .unwrap_or((false, None));
+            interval = if active { POLL_INTERVAL } else { IDLE_INTERVAL };
+
+            let pass = active && bar.is_some_and(|bar| !cursor_over(&window, bar));
+            if pass == ignoring {
+                continue;
+            }
+            ignoring = pass;
+            let _ = window.set_ignore_cursor_events(pass);
+        }
+    });
+}
+
+/// Whether the cursor is over the bar. Unknown positions count as over it,
+/// so the dock never turns click-through by mistake.
+fn cursor_over(window: &tauri::WebviewWindow, bar: BarRect) -> bool {
+    let (Ok(cursor), Ok(origin), Ok(scale)) = (
+        window.cursor_position(),
+        window.inner_position(),
+        window.scale_factor(),
+    ) else {
+        return true;
+    };
+    let x = (cursor.x - origin.x as f64) / scale;
+    let y = (cursor.y - origin.y as f64) / scale;
+    x >= bar.left && x <= bar.right && y >= bar.top && y <= bar.bottom
+}
@@ -1,88 +1,1274 @@
+use log::warn;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
+#[cfg(target_os = "windows")]
+use std::os::windows::ffi::OsStrExt;
 #[cfg(target_os = "windows")]
 use windows::{
+    Win32::Foundation::HANDLE,
     Win32::System::Com::{
-        CLSCTX_ALL, COINIT_APARTMENTTHREADED, CoCreateInstance, CoInitializeEx, IPersistFile,
-        STGM_READ,
+        CLSCTX_ALL, COINIT_APARTMENTTHREADED, CoCreateInstance, CoInitializeEx, CoTaskMemFree,
+        IPersistFile, STGM_READ,
+    },
+    Win32::UI::Shell::{
+        FOLDERID_Desktop, FOLDERID_PublicDesktop, IShellLinkW, KF_FLAG_DEFAULT,
+        SHGetKnownFolderPath, ShellLink,
     },
-    Win32::UI::Shell::{IShellLinkW, ShellLink},
-    core::{ComInterface, PCWSTR},
+    core::{ComInterface, GUID, PCWSTR},
 };
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default)]
 pub struct Shortcut {
     pub name: String,
     pub path: String,
+    /// Image name (`app.exe`) or full path used for running detection
+    /// instead of `path`, for apps whose launcher isn't the long-lived process
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub process_match: Option<String>,
+    #[serde(default, skip_serializing_if = "ShortcutAction::is_launch")]
+    pub action: ShortcutAction,
+    /// Focus the running app's window instead of launching another copy
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub single_instance: bool,
+    /// Question asked (Yes/No) before the shortcut runs, for destructive or
+    /// heavy tools
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confirm: Option<String>,
+    /// Launch elevated, through the UAC prompt; the icon shows a shield
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub run_as_admin: bool,
+    /// Environment variables set for the launched app (and `command`
+    /// actions); values may reference the dock's own variables as `%NAME%`,
+    /// not other entries here. Not applied to `run_as_admin` launches.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env: Option<HashMap<String, String>>,
+    /// Start from an empty environment rather than the dock's, so only `env`
+    /// is set (most Windows apps need at least `SystemRoot`)
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub env_clear: bool,
+    /// Image file (.png, .jpg, ...) or other file whose icon is shown instead
+    /// of `path`'s, e.g. for launch groups
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    /// Which embedded icon of a multi-icon .exe/.dll to show
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_index: Option<i32>,
+    /// Only show the icon while these conditions hold (re-checked every minute)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub visible_when: Option<VisibleWhen>,
+    /// Found at load time (pinned items, category sources) rather than listed
+    /// in the file; such entries are never written back by `save_config`.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub discovered: bool,
+}
+
+/// When a shortcut is shown. Every condition that is set must hold; one that
+/// can't be parsed is ignored (with a warning) rather than hiding the icon.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default, PartialEq)]
+pub struct VisibleWhen {
+    /// Local hours as `"start-end"`, end exclusive: `"9-17"` is 9:00 to
+    /// 16:59, and `"22-6"` wraps past midnight
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hours: Option<String>,
+    /// Weekdays as a range and/or list: `"mon-fri"`, `"sat,sun"`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub days: Option<String>,
+    /// Only while connected to this Wi-Fi network
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssid: Option<String>,
+}
+
+/// What clicking a shortcut does. `path` is the target for `Launch`,
+/// `OpenFolder` and `Url`.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ShortcutAction {
+    #[default]
+    Launch,
+    Command {
+        cmd: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+    OpenFolder,
+    Url,
+    /// Start several apps in order. `delays_ms[i]` is how long to wait after
+    /// starting `apps[i]`, giving heavier apps a head start.
+    LaunchGroup {
+        apps: Vec<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        delays_ms: Vec<u64>,
+    },
+    /// Dock-internal action such as `hide_dock` or `open_settings`
+    Builtin {
+        name: String,
+    },
+    /// Unrecognized `type`; logged and ignored instead of failing the config
+    #[serde(other)]
+    Unknown,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+impl ShortcutAction {
+    fn is_launch(&self) -> bool {
+        *self == ShortcutAction::Launch
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 pub struct Category {
     pub name: String,
+    #[serde(default)]
     pub shortcuts: Vec<Shortcut>,
+    /// Folder or glob (e.g. `D:\Games\*.lnk`) scanned at load time to fill `shortcuts`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Not from the main file (auto-discovered, or a `config.d/` drop-in);
+    /// never written back by `save_config`
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub discovered: bool,
+    /// Disabled categories stay in the file but aren't shown on the dock
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub enabled: bool,
+    /// Show the category as a single folder icon that expands on click
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub collapsed: bool,
+    /// Display order of `shortcuts`; the file order is kept either way
+    #[serde(default, skip_serializing_if = "CategorySort::is_manual")]
+    pub sort: CategorySort,
+}
+
+/// How a category's shortcuts are ordered on the dock.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CategorySort {
+    /// As listed in the config
+    #[default]
+    Manual,
+    NameAsc,
+    NameDesc,
+    /// Most launched first
+    FrequencyDesc,
+}
+
+impl CategorySort {
+    fn is_manual(&self) -> bool {
+        *self == CategorySort::Manual
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+fn is_true(value: &bool) -> bool {
+    *value
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 pub struct Config {
+    /// Editor hint pointing at config.schema.json; kept so saving doesn't
+    /// drop it
+    #[serde(rename = "$schema", default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
     pub categories: Vec<Category>,
+    #[serde(default)]
+    pub style: DockStyle,
+    #[serde(default)]
+    pub accessibility: AccessibilityConfig,
+    /// Write logs to this file (appended) instead of stderr, including what
+    /// was logged while loading the config
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_file: Option<String>,
+    /// Append running apps that aren't pinned after the configured shortcuts
+    #[serde(default)]
+    pub show_running_apps: bool,
+    /// Keep the dock above other windows; when off, normal windows can cover it
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub topmost: bool,
+    /// Get out of the way (hide, drop topmost) while a fullscreen app is active
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub hide_on_fullscreen: bool,
+    /// Say why in a short message on the dock when a launch fails (the icon
+    /// shakes either way)
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub launch_error_toast: bool,
+    /// Let clicks beside and above the bar through to the windows underneath
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub click_through: bool,
+    /// Helper/background executables (image name or full path) never
+    /// reported as running
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub running_ignore: Vec<String>,
+    /// How often the dock checks which apps are running, in ms (default
+    /// 2500, or 10000 in minimal mode; at least 250)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub running_poll_ms: Option<u64>,
+    /// Reuse a process scan younger than this many ms instead of rescanning
+    /// (default 1000)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub running_cache_ms: Option<u64>,
+    /// Cap on the magnification animation's frame rate (30-240); unset
+    /// follows the display's refresh rate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub animation_fps: Option<u32>,
+    /// Utility items (settings, theme switch, clock) pinned at the far end
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub system_items: Vec<SystemItem>,
+    #[serde(default)]
+    pub hotkeys: HotkeyConfig,
+    #[serde(default)]
+    pub discovery: DiscoveryConfig,
+    #[serde(default)]
+    pub recently_closed: RecentlyClosedConfig,
+    /// .wav played when a shortcut is activated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub launch_sound: Option<String>,
+    /// .wav played when magnification moves to another icon
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hover_sound: Option<String>,
+    /// Which taskbars the dock hides: every monitor's, or only its own
+    #[serde(default)]
+    pub hide_taskbars: TaskbarScope,
+    /// Device name of the monitor to keep the dock on (see `list_monitors`,
+    /// e.g. `\\.\DISPLAY2`); unset keeps it on the monitor it's on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monitor: Option<String>,
+    /// Leave the taskbar and work area alone and do less animation/polling
+    #[serde(default)]
+    pub minimal_mode: MinimalMode,
+    /// Start in do-not-disturb mode: no highlight pulses, bounces or sounds
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub do_not_disturb: bool,
+    /// What clicking the icon of the app that already has focus does
+    #[serde(default)]
+    pub click_focused_action: ClickFocusedAction,
+    /// Kiosk use: hide the dock after this many seconds without interaction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle_hide_secs: Option<u64>,
+    /// Kiosk use: quit after this many seconds without interaction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle_quit_secs: Option<u64>,
+}
+
+// Manual so that defaults match the serde ones for a missing/broken file
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            schema: None,
+            categories: Vec::new(),
+            style: DockStyle::default(),
+            accessibility: AccessibilityConfig::default(),
+            log_file: None,
+            show_running_apps: false,
+            topmost: true,
+            hide_on_fullscreen: true,
+            monitor: None,
+            launch_error_toast: true,
+            click_through: true,
+            running_ignore: Vec::new(),
+            running_poll_ms: None,
+            running_cache_ms: None,
+            animation_fps: None,
+            system_items: Vec::new(),
+            hotkeys: HotkeyConfig::default(),
+            discovery: DiscoveryConfig::default(),
+            recently_closed: RecentlyClosedConfig::default(),
+            launch_sound: None,
+            hover_sound: None,
+            hide_taskbars: TaskbarScope::default(),
+            minimal_mode: MinimalMode::default(),
+            do_not_disturb: false,
+            click_focused_action: ClickFocusedAction::default(),
+            idle_hide_secs: None,
+            idle_quit_secs: None,
+        }
+    }
+}
+
+/// Visual options for the dock.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default)]
+pub struct DockStyle {
+    #[serde(default)]
+    pub icon_shape: IconShape,
+    #[serde(default)]
+    pub theme: DockTheme,
+    /// What shows through the bar: the desktop as-is, or blurred
+    #[serde(default)]
+    pub backdrop: Backdrop,
+    /// Names only as hover tooltips, or always shown under the icons
+    #[serde(default)]
+    pub label_mode: LabelMode,
+    /// Label/tooltip font; falls back to the system UI font when not installed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub font_family: Option<String>,
+    /// Label/tooltip font size in CSS pixels
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub font_size: Option<f32>,
+    /// Labels wider than this (CSS pixels) are cut off with "…" until hovered
+    /// for a moment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label_max_width: Option<f32>,
+    /// Alpha of the bar's background colour, 0.0-1.0 (out-of-range values
+    /// are clamped)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub background_opacity: Option<f32>,
+    /// Compact mode: icon scale (0.3-1.0) while the dock isn't hovered; they
+    /// grow to full size when the cursor enters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compact_scale: Option<f32>,
+    /// CSS colour of the selection ring; follows the Windows accent colour
+    /// when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accent_color: Option<String>,
+    /// Extra clickable/hoverable margin around each icon, in logical pixels;
+    /// neighbours split the gap between them evenly
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hit_padding: Option<f32>,
+    /// Tilt the icon row slightly toward the cursor (off under reduced motion)
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub tilt_effect: bool,
+    /// Faded mirror image of each icon just below the bar
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub reflections: bool,
+    /// Inset from the left/right screen edges, in logical pixels
+    #[serde(default)]
+    pub margin_x: i32,
+    /// Gap between the dock and the bottom screen edge, in logical pixels
+    #[serde(default)]
+    pub margin_y: i32,
+}
+
+/// Runtime overrides layered over `DockStyle` by `set_style_override`; unset
+/// fields keep the config's value. Never persisted.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PartialDockStyle {
+    pub icon_shape: Option<IconShape>,
+    pub theme: Option<DockTheme>,
+    pub backdrop: Option<Backdrop>,
+    pub label_mode: Option<LabelMode>,
+    pub font_family: Option<String>,
+    pub font_size: Option<f32>,
+    pub label_max_width: Option<f32>,
+    pub background_opacity: Option<f32>,
+    pub compact_scale: Option<f32>,
+    pub accent_color: Option<String>,
+    pub hit_padding: Option<f32>,
+    pub tilt_effect: Option<bool>,
+    pub reflections: Option<bool>,
+    pub margin_x: Option<i32>,
+    pub margin_y: Option<i32>,
+}
+
+impl PartialDockStyle {
+    /// Take every field `other` sets, keeping the rest.
+    pub fn merge(&mut self, other: PartialDockStyle) {
+        self.icon_shape = other.icon_shape.or(self.icon_shape);
+        self.theme = other.theme.or(self.theme);
+        self.backdrop = other.backdrop.or(self.backdrop);
+        self.label_mode = other.label_mode.or(self.label_mode);
+        self.font_family = other.font_family.or(self.font_family.take());
+        self.font_size = other.font_size.or(self.font_size);
+        self.label_max_width = other.label_max_width.or(self.label_max_width);
+        self.background_opacity = other.background_opacity.or(self.background_opacity);
+        self.compact_scale = other.compact_scale.or(self.compact_scale);
+        self.accent_color = other.accent_color.or(self.accent_color.take());
+        self.hit_padding = other.hit_padding.or(self.hit_padding);
+        self.tilt_effect = other.tilt_effect.or(self.tilt_effect);
+        self.reflections = other.reflections.or(self.reflections);
+        self.margin_x = other.margin_x.or(self.margin_x);
+        self.margin_y = other.margin_y.or(self.margin_y);
+    }
+
+    /// `style` with these overrides applied.
+    pub fn apply(&self, style: &DockStyle) -> DockStyle {
+        DockStyle {
+            icon_shape: self.icon_shape.unwrap_or(style.icon_shape),
+            theme: self.theme.unwrap_or(style.theme),
+            backdrop: self.backdrop.unwrap_or(style.backdrop),
+            label_mode: self.label_mode.unwrap_or(style.label_mode),
+            font_family: self
+                .font_family
+                .clone()
+                .or_else(|| style.font_family.clone()),
+            font_size: self.font_size.or(style.font_size),
+            label_max_width: self.label_max_width.or(style.label_max_width),
+            background_opacity: self.background_opacity.or(style.background_opacity),
+            compact_scale: self.compact_scale.or(style.compact_scale),
+            accent_color: self
+                .accent_color
+                .clone()
+                .or_else(|| style.accent_color.clone()),
+            hit_padding: self.hit_padding.or(style.hit_padding),
+            tilt_effect: self.tilt_effect.unwrap_or(style.tilt_effect),
+            reflections: self.reflections.unwrap_or(style.reflections),
+            margin_x: self.margin_x.unwrap_or(style.margin_x),
+            margin_y: self.margin_y.unwrap_or(style.margin_y),
+        }
+    }
 }
 
+/// Colour scheme of the bar.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DockTheme {
+    /// Dark glass
+    #[default]
+    Dark,
+    Light,
+}
+
+/// Effect behind the dock window.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Backdrop {
+    /// No effect; the bar's tinted background over the desktop
+    #[default]
+    Flat,
+    /// System blur of the desktop under the dock strip. Cheaper than acrylic,
+    /// but covers the whole strip rather than just the rounded bar, and its
+    /// radius is fixed by Windows.
+    Blur,
+}
+
+/// Built-in item in the utility strip at the end of the dock. These stay in
+/// the configured order and aren't magnified.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SystemItem {
+    /// Opens the settings window
+    Settings,
+    /// Switches between the dark and light theme until restart
+    ToggleTheme,
+    /// Current time, with the date in its tooltip
+    Clock,
+    /// Unrecognized `type`; ignored instead of failing the config
+    #[serde(other)]
+    Unknown,
+}
+
+/// Whether app names are shown under the icons.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LabelMode {
+    /// Names only appear as tooltips on hover
+    #[default]
+    IconsOnly,
+    IconsAndLabels,
+}
+
+impl LabelMode {
+    pub fn toggled(self) -> Self {
+        match self {
+            LabelMode::IconsOnly => LabelMode::IconsAndLabels,
+            LabelMode::IconsAndLabels => LabelMode::IconsOnly,
+        }
+    }
+}
+
+/// Mask applied to each icon.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum IconShape {
+    /// Artwork as extracted
+    #[default]
+    Square,
+    Rounded,
+    Circle,
+}
+
+/// Overrides for the system accessibility settings; `None` follows Windows.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default)]
+pub struct AccessibilityConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reduced_motion: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub high_contrast: Option<bool>,
+}
+
+/// Extra places scanned for shortcuts at load time, each shown as its own
+/// category after the taskbar's "Pinned" one.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default)]
+pub struct DiscoveryConfig {
+    /// The user's and the public desktop
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub desktop: bool,
+    /// The user's and the All Users Start Menu programs
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub start_menu: bool,
+    /// Targets the user removed from a discovered category; never rediscovered.
+    /// (Editing a discovered category promotes it to a regular one, whose
+    /// targets are then skipped by discovery as well.)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hidden: Vec<String>,
+}
+
+/// A transient "Recently Closed" section of shortcuts whose app exited
+/// lately, for a quick relaunch. Off while `size` is 0.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default)]
+pub struct RecentlyClosedConfig {
+    /// Most apps listed at once; the oldest drop off first
+    #[serde(default)]
+    pub size: usize,
+    /// Minutes an app stays listed after closing (default 10)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expire_minutes: Option<u64>,
+}
+
+/// Taskbars hidden while the dock runs.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskbarScope {
+    /// The primary taskbar and those on secondary monitors
+    #[default]
+    All,
+    /// Only the taskbar on the dock's monitor
+    DockMonitor,
+}
+
+/// When the lightweight minimal mode is used.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MinimalMode {
+    #[default]
+    Off,
+    On,
+    /// Only while running on battery, switching back when plugged in
+    OnBattery,
+}
+
+/// Click on the icon of the app that was in the foreground.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ClickFocusedAction {
+    /// Same as any other click (focus it, or launch per the shortcut)
+    #[default]
+    Nothing,
+    /// Minimize its windows, like a taskbar button
+    Minimize,
+    /// Keep it and minimize every other app's windows
+    MinimizeOthers,
+}
+
+/// Global key combos (e.g. `"Ctrl+Alt+D"`) for core dock actions; unset
+/// actions have no hotkey.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default)]
+pub struct HotkeyConfig {
+    /// Re-read the config file and rebuild the dock
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reload: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub toggle_hidden: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quit: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub open_settings: Option<String>,
+    /// Pin the app in the foreground to the first regular category
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pin_foreground: Option<String>,
+    /// Re-extract all icons without reloading the config
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reload_icons: Option<String>,
+    /// Switch between icons only and icons with labels
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub toggle_labels: Option<String>,
+}
+
+/// Pauses between attempts to read a config that's briefly unreachable (a
+/// network share not mounted yet at login, an editor mid-save): ~4s in all.
+const READ_RETRY_DELAYS: [Duration; 4] = [
+    Duration::from_millis(250),
+    Duration::from_millis(500),
+    Duration::from_millis(1000),
+    Duration::from_millis(2000),
+];
+
+/// Load the config, failing straight away if the file can't be read. Used
+/// on every path the UI waits on (reload, save, import).
 pub fn load_config<P: AsRef<Path>>(path: P) -> Result<Config, Box<dyn std::error::Error>> {
-    let mut config = if path.as_ref().exists() {
-        let content = fs::read_to_string(path)?;
-        serde_json::from_str(&content)?
+    load(path.as_ref(), false)
+}
+
+/// Load the config, retrying a transiently unreachable file for a few
+/// seconds first. Only for startup and the background retry, where a short
+/// wait beats starting with an empty dock; never on the event loop.
+pub fn load_config_with_retry<P: AsRef<Path>>(
+    path: P,
+) -> Result<Config, Box<dyn std::error::Error>> {
+    load(path.as_ref(), true)
+}
+
+fn load(path: &Path, retry: bool) -> Result<Config, Box<dyn std::error::Error>> {
+    let mut config = crate::profile::time(
+        "config read",
+        || -> Result<Config, Box<dyn std::error::Error>> {
+            match read_config_file(path, retry)? {
+                // A parse error is permanent, so it is never retried
+                Some(content) => Ok(serde_json::from_str(&content)?),
+                None => Ok(Config::default()),
+            }
+        },
+    )?;
+
+    crate::profile::time("config.d drop-ins", || load_drop_ins(path, &mut config));
+    crate::profile::time("category sources", || expand_sources(&mut config));
+    crate::profile::time("discovery", || discover(&mut config));
+
+    Ok(config)
+}
+
+/// Read the config file, retrying transient failures with backoff if `retry`
+/// is set. `None` means there's no config yet (first run), as opposed to a
+/// folder that can't be reached right now.
+fn read_config_file(path: &Path, retry: bool) -> io::Result<Option<String>> {
+    let mut delays = READ_RETRY_DELAYS.iter().filter(|_| retry);
+    loop {
+        let missing_dir = path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty() && !dir.exists());
+        let result = if path.exists() {
+            fs::read_to_string(path).map(Some)
+        } else if let Some(dir) = missing_dir {
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("config folder {:?} is unreachable", dir),
+            ))
+        } else {
+            Ok(None)
+        };
+        match result {
+            Err(e) if is_transient(&e) => match delays.next() {
+                Some(delay) => std::thread::sleep(*delay),
+                None => return Err(e),
+            },
+            result => return result,
+        }
+    }
+}
+
+/// Whether a failed config read may succeed if tried again later.
+pub fn is_transient(e: &io::Error) -> bool {
+    use io::ErrorKind::*;
+    if matches!(
+        e.kind(),
+        NotFound
+            | TimedOut
+            | Interrupted
+            | WouldBlock
+            | ConnectionReset
+            | ConnectionAborted
+            | NotConnected
+    ) {
+        return true;
+    }
+    // Sharing/lock violations, and the network path/name/timeout errors a
+    // share that's dropping in and out produces
+    cfg!(target_os = "windows")
+        && matches!(
+            e.raw_os_error(),
+            Some(32 | 33 | 53 | 59 | 64 | 67 | 121 | 1231)
+        )
+}
+
+/// Expand folder/glob sources, keeping explicitly listed shortcuts first.
+fn expand_sources(config: &mut Config) {
+    for category in &mut config.categories {
+        let Some(source) = category.source.as_deref() else {
+            continue;
+        };
+        for shortcut in scan_source(source) {
+            let exists = category
+                .shortcuts
+                .iter()
+                .any(|s| s.path.eq_ignore_ascii_case(&shortcut.path));
+            if !exists {
+                category.shortcuts.push(shortcut);
+            }
+        }
+    }
+}
+
+/// Auto-discover pinned items (plus the opted-in sources), each as its own
+/// category if not empty. An app found by several sources shows up once.
+fn discover(config: &mut Config) {
+    let mut sources = vec![("Pinned", discover_pinned_items())];
+    if config.discovery.desktop {
+        sources.push(("Desktop", discover_desktop_items()));
+    }
+    if config.discovery.start_menu {
+        sources.push(("Start Menu", discover_start_menu_items()));
+    }
+    // Targets already listed explicitly (e.g. promoted from a discovered
+    // category) or hidden by the user aren't discovered again
+    let mut seen: HashSet<String> = config
+        .categories
+        .iter()
+        .flat_map(|c| &c.shortcuts)
+        .map(|s| s.path.to_lowercase())
+        .chain(config.discovery.hidden.iter().map(|p| p.to_lowercase()))
+        .collect();
+    for (name, mut shortcuts) in sources {
+        shortcuts.retain(|s| seen.insert(s.path.to_lowercase()));
+        if !shortcuts.is_empty() {
+            config.categories.push(Category {
+                name: name.to_string(),
+                shortcuts,
+                source: None,
+                discovered: true,
+                enabled: true,
+                collapsed: false,
+                sort: CategorySort::Manual,
+            });
+        }
+    }
+}
+
+/// Write `config` to disk in the same form as [`to_json`].
+pub fn save_config<P: AsRef<Path>>(
+    path: P,
+    config: &Config,
+) -> Result<(), Box<dyn std::error::Error>> {
+    fs::write(path, to_json(config)?)?;
+    Ok(())
+}
+
+/// JSON Schema of the config format, for editor validation and completion
+/// (reference it from config.json via `"$schema"`).
+pub fn schema_json() -> String {
+    let schema = schemars::schema_for!(Config);
+    serde_json::to_string_pretty(&schema).unwrap_or_default()
+}
+
+/// Write the schema next to the config file, replacing one an older version
+/// wrote so editors know about newly added fields.
+pub fn write_schema(config_path: &Path) {
+    let path = config_path.with_file_name("config.schema.json");
+    let schema = schema_json();
+    if fs::read_to_string(&path).is_ok_and(|current| current == schema) {
+        return;
+    }
+    if let Err(e) = fs::write(&path, schema) {
+        warn!("Failed to write {:?}: {}", path, e);
+    }
+}
+
+/// Pretty JSON of `config`, leaving out everything discovered at load time so
+/// pinned items and folder scans stay live instead of being baked in.
+pub fn to_json(config: &Config) -> Result<String, serde_json::Error> {
+    let mut config = config.clone();
+    config.categories.retain(|c| !c.discovered);
+    for category in &mut config.categories {
+        category.shortcuts.retain(|s| !s.discovered);
+    }
+    serde_json::to_string_pretty(&config)
+}
+
+/// Shortest `running_poll_ms` accepted; the dock clamps to it as well.
+const MIN_RUNNING_POLL_MS: u64 = 250;
+
+/// Reject configs that would produce a broken dock, naming the first problem.
+pub fn validate(config: &Config) -> Result<(), String> {
+    // Categories are looked up by name (set_category_flags, renames);
+    // discovered ones aren't saved, so only saved names must be unique
+    let mut names = HashSet::new();
+    for (index, category) in config.categories.iter().enumerate() {
+        if category.name.trim().is_empty() {
+            return Err(format!("category #{} has an empty name", index + 1));
+        }
+        if !category.discovered && !names.insert(category.name.trim().to_lowercase()) {
+            return Err(format!(
+                "there is more than one category named '{}'",
+                category.name
+            ));
+        }
+        for shortcut in &category.shortcuts {
+            let needs_path = matches!(
+                shortcut.action,
+                ShortcutAction::Launch | ShortcutAction::OpenFolder | ShortcutAction::Url
+            );
+            if needs_path && shortcut.path.trim().is_empty() {
+                return Err(format!(
+                    "shortcut '{}' in '{}' has an empty path",
+                    shortcut.name, category.name
+                ));
+            }
+            if let ShortcutAction::Command { cmd, .. } = &shortcut.action {
+                if cmd.trim().is_empty() {
+                    return Err(format!(
+                        "shortcut '{}' in '{}' has an empty command",
+                        shortcut.name, category.name
+                    ));
+                }
+            }
+            if let ShortcutAction::LaunchGroup { apps, .. } = &shortcut.action {
+                if apps.iter().all(|app| app.trim().is_empty()) {
+                    return Err(format!(
+                        "launch group '{}' in '{}' has no apps",
+                        shortcut.name, category.name
+                    ));
+                }
+            }
+        }
+    }
+
+    if let Some(size) = config.style.font_size {
+        if !(size.is_finite() && size > 0.0) {
+            return Err(format!("style.font_size must be positive, got {}", size));
+        }
+    }
+    // Zero would hide or quit on the first mouse move, locking a kiosk out
+    if config.idle_hide_secs == Some(0) {
+        return Err("idle_hide_secs must be at least 1".to_string());
+    }
+    if config.idle_quit_secs == Some(0) {
+        return Err("idle_quit_secs must be at least 1".to_string());
+    }
+    if let Some(ms) = config.running_poll_ms {
+        if ms < MIN_RUNNING_POLL_MS {
+            return Err(format!(
+                "running_poll_ms must be at least {}, got {}",
+                MIN_RUNNING_POLL_MS, ms
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// A `config.d/*.json` file: extra categories merged into the main config.
+#[derive(Deserialize)]
+struct DropIn {
+    #[serde(default)]
+    categories: Vec<Category>,
+}
+
+/// Append the categories of every `*.json` in the `config.d` directory next to
+/// the main config, in file name order. Broken files are logged and skipped,
+/// as are categories the main file already has (e.g. after being edited in
+/// the settings window, which saves them there).
+fn load_drop_ins(config_path: &Path, config: &mut Config) {
+    let dir = config_path.with_file_name("config.d");
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return;
+    };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| {
+            p.extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+        })
+        .collect();
+    files.sort();
+
+    for file in files {
+        let drop_in: DropIn = match fs::read_to_string(&file)
+            .map_err(|e| e.to_string())
+            .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string()))
+        {
+            Ok(drop_in) => drop_in,
+            Err(e) => {
+                warn!("Skipping drop-in config {:?}: {}", file, e);
+                continue;
+            }
+        };
+        for mut category in drop_in.categories {
+            if config.categories.iter().any(|c| c.name == category.name) {
+                warn!(
+                    "Drop-in {:?}: category '{}' already exists, skipping",
+                    file, category.name
+                );
+                continue;
+            }
+            category.discovered = true;
+            config.categories.push(category);
+        }
+    }
+}
+
+/// Scan a category `source`. A plain directory yields its `.lnk`/`.exe` files;
+/// otherwise the last path component is treated as a `*`/`?` wildcard pattern.
+fn scan_source(source: &str) -> Vec<Shortcut> {
+    let source_path = Path::new(source);
+    let (dir, pattern) = if source_path.is_dir() {
+        (source_path, None)
     } else {
-        Config { categories: vec![] }
+        match (
+            source_path.parent(),
+            source_path.file_name().and_then(|s| s.to_str()),
+        ) {
+            (Some(dir), Some(pattern)) => (dir, Some(pattern.to_lowercase())),
+            _ => return Vec::new(),
+        }
     };
 
-    // Auto-discover pinned items and add them as a "Pinned" category if not empty
-    let pinned = discover_pinned_items();
-    if !pinned.is_empty() {
-        config.categories.push(Category {
-            name: "Pinned".to_string(),
-            shortcuts: pinned,
-        });
+    let Ok(entries) = fs::read_dir(dir) else {
+        warn!("Category source not readable: {:?}", dir);
+        return Vec::new();
+    };
+
+    #[cfg(target_os = "windows")]
+    unsafe {
+        let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
     }
 
-    Ok(config)
+    let mut shortcuts = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+        let Some(file_name) = path.file_name().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        let extension = path
+            .extension()
+            .and_then(|s| s.to_str())
+            .map(|s| s.to_lowercase());
+
+        let matches = match &pattern {
+            Some(pattern) => wildcard_match(pattern, &file_name.to_lowercase()),
+            None => matches!(extension.as_deref(), Some("lnk") | Some("exe")),
+        };
+        if !matches {
+            continue;
+        }
+
+        let target = if extension.as_deref() == Some("lnk") {
+            #[cfg(target_os = "windows")]
+            let resolved = resolve_shortcut(&path);
+            #[cfg(not(target_os = "windows"))]
+            let resolved = None;
+            resolved
+        } else {
+            path.to_str().map(|s| s.to_string())
+        };
+
+        if let Some(target) = target {
+            let name = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("Unknown")
+                .to_string();
+            shortcuts.push(Shortcut {
+                name,
+                path: target,
+                discovered: true,
+                ..Default::default()
+            });
+        }
+    }
+
+    // read_dir order is unspecified; keep the dock stable between runs
+    shortcuts.sort_by_key(|s| s.name.to_lowercase());
+    shortcuts
+}
+
+/// Minimal glob matcher supporting `*` (any run) and `?` (any single char).
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            // Let the last `*` swallow one more character and retry
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
 }
 
 fn discover_pinned_items() -> Vec<Shortcut> {
+    let Ok(appdata) = std::env::var("APPDATA") else {
+        return Vec::new();
+    };
+    let pinned_path =
+        Path::new(&appdata).join(r"Microsoft\Internet Explorer\Quick Launch\User Pinned\TaskBar");
+    // Chosen by the user, so kept even if it looks like a readme
+    discover_links(&[pinned_path], false)
+}
+
+/// The user's and the public desktop, wherever they've been redirected to
+/// (e.g. into OneDrive).
+fn discover_desktop_items() -> Vec<Shortcut> {
+    #[cfg(target_os = "windows")]
+    let dirs: Vec<PathBuf> = [FOLDERID_Desktop, FOLDERID_PublicDesktop]
+        .iter()
+        .filter_map(known_folder_path)
+        .collect();
+    #[cfg(not(target_os = "windows"))]
+    let dirs: Vec<PathBuf> = Vec::new();
+    without_auxiliary(discover_links(&dirs, false))
+}
+
+#[cfg(target_os = "windows")]
+fn known_folder_path(id: &GUID) -> Option<PathBuf> {
+    unsafe {
+        let folder = SHGetKnownFolderPath(id, KF_FLAG_DEFAULT, HANDLE::default()).ok()?;
+        let path = folder.to_string().ok();
+        CoTaskMemFree(Some(folder.0 as *const _));
+        path.map(PathBuf::from)
+    }
+}
+
+/// Drop the uninstallers, readmes and other non-app links installers leave
+/// on the desktop and in the Start Menu.
+fn without_auxiliary(mut shortcuts: Vec<Shortcut>) -> Vec<Shortcut> {
+    shortcuts.retain(|s| !is_auxiliary_link(&s.name, &s.path));
+    shortcuts
+}
+
+/// The user's and the All Users Start Menu, including program subfolders.
+fn discover_start_menu_items() -> Vec<Shortcut> {
+    let dirs: Vec<PathBuf> = ["APPDATA", "ProgramData"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .map(|dir| Path::new(&dir).join(r"Microsoft\Windows\Start Menu\Programs"))
+        .collect();
+    let mut shortcuts = without_auxiliary(discover_links(&dirs, true));
+    shortcuts.sort_by_key(|s| s.name.to_lowercase());
+    shortcuts
+}
+
+/// Executables a starter config picks from the Start Menu, in dock order:
+/// browsers, editors, then terminals. At most one app per group.
+const STARTER_APPS: [&[&str]; 3] = [
+    &[
+        "msedge.exe",
+        "chrome.exe",
+        "firefox.exe",
+        "brave.exe",
+        "opera.exe",
+    ],
+    &["code.exe", "notepad++.exe", "sublime_text.exe"],
+    &["pwsh.exe", "powershell.exe"],
+];
+
+/// Category holding the starter config's shortcuts.
+const STARTER_CATEGORY: &str = "Apps";
+
+/// First run: with no config at `path`, write one with File Explorer and the
+/// commonly used apps found in the Start Menu (a browser, an editor, a
+/// terminal), so a new dock isn't empty. Returns how many shortcuts it got,
+/// or `None` when a config already exists.
+pub fn write_starter_config(path: &Path) -> Option<Result<usize, String>> {
+    if path.exists() {
+        return None;
+    }
+
+    let mut shortcuts = Vec::new();
+    if let Ok(windir) = std::env::var("WINDIR") {
+        let explorer = Path::new(&windir).join("explorer.exe");
+        if explorer.exists() {
+            shortcuts.push(Shortcut {
+                name: "File Explorer".to_string(),
+                path: explorer.to_string_lossy().into_owned(),
+                ..Default::default()
+            });
+        }
+    }
+
+    let start_menu = discover_start_menu_items();
+    let file_name = |s: &Shortcut| {
+        Path::new(&s.path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_lowercase())
+    };
+    for group in STARTER_APPS {
+        // The group's first choice that's installed
+        let found = group.iter().find_map(|exe| {
+            start_menu
+                .iter()
+                .find(|s| file_name(s).as_deref() == Some(*exe))
+        });
+        if let Some(shortcut) = found {
+            shortcuts.push(Shortcut {
+                discovered: false,
+                ..shortcut.clone()
+            });
+        }
+    }
+
+    let count = shortcuts.len();
+    let config = Config {
+        categories: vec![Category {
+            name: STARTER_CATEGORY.to_string(),
+            shortcuts,
+            source: None,
+            discovered: false,
+            enabled: true,
+            collapsed: false,
+            sort: CategorySort::Manual,
+        }],
+        ..Config::default()
+    };
+    Some(
+        save_config(path, &config)
+            .map(|()| count)
+            .map_err(|e| e.to_string()),
+    )
+}
+
+/// Resolve the `.lnk` files in `dirs` (and, with `recursive`, their
+/// subfolders).
+fn discover_links(dirs: &[PathBuf], recursive: bool) -> Vec<Shortcut> {
     let mut shortcuts = Vec::new();
 
     #[cfg(target_os = "windows")]
     {
-        if let Ok(appdata) = std::env::var("APPDATA") {
-            let pinned_path = Path::new(&appdata)
-                .join(r"Microsoft\Internet Explorer\Quick Launch\User Pinned\TaskBar");
-            if let Ok(entries) = fs::read_dir(pinned_path) {
-                // Initialize COM for shortcut resolution
-                unsafe {
-                    let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
-                }
+        // Initialize COM for shortcut resolution
+        unsafe {
+            let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+        }
 
-                for entry in entries.flatten() {
-                    let path = entry.path();
-                    if path.extension().and_then(|s| s.to_str()) == Some("lnk") {
-                        if let Some(target) = resolve_shortcut(&path) {
-                            let name = path
-                                .file_stem()
-                                .and_then(|s| s.to_str())
-                                .unwrap_or("Unknown")
-                                .to_string();
-                            shortcuts.push(Shortcut { name, path: target });
-                        }
+        // Depth-first in the order given, so results don't depend on how
+        // the filesystem happens to list things
+        let mut pending: Vec<PathBuf> = dirs.iter().rev().cloned().collect();
+        while let Some(dir) = pending.pop() {
+            let Some(paths) = sorted_entries(&dir) else {
+                continue;
+            };
+            let mut subdirs = Vec::new();
+            for path in paths {
+                if path.is_dir() {
+                    if recursive {
+                        subdirs.push(path);
                     }
+                    continue;
                 }
+                if path.extension().and_then(|s| s.to_str()) != Some("lnk") {
+                    continue;
+                }
+                let Some(target) = resolve_shortcut(&path) else {
+                    continue;
+                };
+                let name = path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or("Unknown")
+                    .to_string();
+                shortcuts.push(Shortcut {
+                    name,
+                    path: target,
+                    discovered: true,
+                    ..Default::default()
+                });
             }
+            pending.extend(subdirs.into_iter().rev());
         }
     }
+    #[cfg(not(target_os = "windows"))]
+    let _ = (dirs, recursive);
 
     shortcuts
 }
 
+/// The entries of `dir` sorted by file name, case-insensitively. `read_dir`
+/// order varies between filesystems, which would reshuffle the dock.
+fn sorted_entries(dir: &Path) -> Option<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .collect();
+    paths.sort_by_cached_key(|path| {
+        path.file_name()
+            .map(|name| name.to_string_lossy().to_lowercase())
+    });
+    Some(paths)
+}
+
+/// Links installers drop next to the real app: uninstallers, readmes,
+/// manuals and the like.
+fn is_auxiliary_link(name: &str, target: &str) -> bool {
+    const NAME_HINTS: [&str; 6] = [
+        "uninstall",
+        "readme",
+        "read me",
+        "license",
+        "release notes",
+        "documentation",
+    ];
+    const DOC_EXTENSIONS: [&str; 6] = ["txt", "pdf", "chm", "htm", "html", "rtf"];
+
+    let name = name.to_lowercase();
+    let is_doc = Path::new(target)
+        .extension()
+        .and_then(|s| s.to_str())
+        .is_some_and(|ext| DOC_EXTENSIONS.iter().any(|d| ext.eq_ignore_ascii_case(d)));
+    is_doc || NAME_HINTS.iter().any(|hint| name.contains(hint))
+}
+
+/// Replace `%NAME%` references with the dock's environment variables, as
+/// Windows does. Unknown names are left as written.
+pub fn expand_env_vars(value: &str) -> String {
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('%') {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('%') else {
+            expanded.push_str(&rest[start..]);
+            return expanded;
+        };
+        match std::env::var(&after[..end]) {
+            Ok(var) if end > 0 => {
+                expanded.push_str(&var);
+                rest = &after[end + 1..];
+            }
+            // Not a variable: keep this '%' and look for one from the next
+            _ => {
+                expanded.push('%');
+                rest = after;
+            }
+        }
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+/// What a shortcut points at: the target of a `.lnk`, else `path` itself.
+pub fn shortcut_target(path: &str) -> String {
+    let is_lnk = Path::new(path)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("lnk"));
+    #[cfg(target_os = "windows")]
+    if is_lnk {
+        if let Some(target) = resolve_shortcut(Path::new(path)) {
+            return target;
+        }
+    }
+    #[cfg(not(target_os = "windows"))]
+    let _ = is_lnk;
+    path.to_string()
+}
+
+/// Longest path Windows supports, in UTF-16 units.
+#[cfg(target_os = "windows")]
+const LONG_PATH_LEN: usize = 32_768;
+
 #[cfg(target_os = "windows")]
 fn resolve_shortcut(lnk_path: &Path) -> Option<String> {
     unsafe {
@@ -90,21 +1276,23 @@ fn resolve_shortcut(lnk_path: &Path) -> Option<String> {
         let persist_file: IPersistFile = shell_link.cast().ok()?;
 
         let path_wide: Vec<u16> = lnk_path
-            .to_str()?
-            .encode_utf16()
+            .as_os_str()
+            .encode_wide()
             .chain(std::iter::once(0))
             .collect();
         persist_file
             .Load(PCWSTR(path_wide.as_ptr()), STGM_READ)
             .ok()?;
 
-        let mut buffer = [0u16; 260];
+        // Room for long paths: MAX_PATH counts UTF-16 units, and emoji and
+        // other astral characters take two each
+        let mut buffer = vec![0u16; LONG_PATH_LEN];
         if shell_link
             .GetPath(&mut buffer, std::ptr::null_mut(), 0)
             .is_ok()
         {
-            let target = String::from_utf16_lossy(&buffer);
-            let target = target.trim_matches('\0').to_string();
+            let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+            let target = String::from_utf16_lossy(&buffer[..len]);
             if !target.is_empty() {
                 return Some(target);
             }
@@ -112,3 +1300,209 @@ fn resolve_shortcut(lnk_path: &Path) -> Option<String> {
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh, empty directory under the system temp dir.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("oxidedock-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+
+    /// Write a `.lnk` at `lnk` pointing at `target`.
+    #[cfg(target_os = "windows")]
+    fn create_link(lnk: &Path, target: &Path) {
+        use windows::Win32::Foundation::TRUE;
+
+        let wide = |path: &Path| -> Vec<u16> {
+            path.as_os_str()
+                .encode_wide()
+                .chain(std::iter::once(0))
+                .collect()
+        };
+        unsafe {
+            let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+            let link: IShellLinkW = CoCreateInstance(&ShellLink, None, CLSCTX_ALL).unwrap();
+            let target_wide = wide(target);
+            link.SetPath(PCWSTR(target_wide.as_ptr())).unwrap();
+            let file: IPersistFile = link.cast().unwrap();
+            let lnk_wide = wide(lnk);
+            file.Save(PCWSTR(lnk_wide.as_ptr()), TRUE).unwrap();
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn resolves_shortcuts_with_cjk_and_emoji_names() {
+        let root = temp_dir("unicode-lnk");
+        let dir = root.join("ドック 📁");
+        fs::create_dir_all(&dir).unwrap();
+        let target = dir.join("メモ帳 🚀 テスト.txt");
+        fs::write(&target, "").unwrap();
+        let lnk = dir.join("ショートカット 🎉.lnk");
+        create_link(&lnk, &target);
+
+        assert_eq!(resolve_shortcut(&lnk).as_deref(), target.to_str());
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn sorts_entries_by_name_ignoring_case() {
+        let dir = temp_dir("sorted-entries");
+        for name in ["beta.lnk", "Alpha.lnk", "delta.lnk", "Charlie.lnk"] {
+            fs::write(dir.join(name), "").unwrap();
+        }
+        fs::create_dir(dir.join("Bravo")).unwrap();
+
+        let names: Vec<String> = sorted_entries(&dir)
+            .unwrap()
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(
+            names,
+            ["Alpha.lnk", "beta.lnk", "Bravo", "Charlie.lnk", "delta.lnk"]
+        );
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn discovers_links_depth_first_in_name_order() {
+        let root = temp_dir("discover-links");
+        let target = root.join("app.exe");
+        fs::write(&target, "").unwrap();
+
+        // Two roots, given second-first; each lists its own links before
+        // descending into its subfolders, in name order
+        let first = root.join("first");
+        let second = root.join("second");
+        for dir in [
+            first.join("b-sub"),
+            first.join("A-sub").join("nested"),
+            second.clone(),
+        ] {
+            fs::create_dir_all(dir).unwrap();
+        }
+        for lnk in [
+            first.join("zulu.lnk"),
+            first.join("Echo.lnk"),
+            first.join("b-sub").join("Bee.lnk"),
+            first.join("A-sub").join("ay.lnk"),
+            first.join("A-sub").join("nested").join("Deep.lnk"),
+            second.join("Second.lnk"),
+        ] {
+            create_link(&lnk, &target);
+        }
+
+        let names: Vec<String> = discover_links(&[second, first.clone()], true)
+            .into_iter()
+            .map(|shortcut| shortcut.name)
+            .collect();
+        let flat: Vec<String> = discover_links(&[first], false)
+            .into_iter()
+            .map(|shortcut| shortcut.name)
+            .collect();
+        let _ = fs::remove_dir_all(&root);
+
+        assert_eq!(names, ["Second", "Echo", "zulu", "ay", "Deep", "Bee"]);
+        assert_eq!(flat, ["Echo", "zulu"]);
+    }
+
+    #[test]
+    fn empty_configs_validate_and_build_no_items() {
+        for json in [
+            "{}",
+            r#"{"categories": []}"#,
+            r#"{"categories": [{"name": "Apps"}]}"#,
+        ] {
+            let config: Config = serde_json::from_str(json).unwrap();
+            assert!(validate(&config).is_ok(), "{}", json);
+
+            // The frontend shows its "no shortcuts" hint for this
+            let model = crate::model::build(&config, &HashMap::new(), &[], &[]);
+            let items: usize = model.categories.iter().map(|c| c.shortcuts.len()).sum();
+            assert_eq!(items, 0, "{}", json);
+        }
+    }
+
+    #[test]
+    fn rejects_running_poll_below_minimum() {
+        let config: Config = serde_json::from_str(r#"{"running_poll_ms": 0}"#).unwrap();
+        assert!(validate(&config).is_err());
+        let config: Config = serde_json::from_str(r#"{"running_poll_ms": 250}"#).unwrap();
+        assert!(validate(&config).is_ok());
+    }
+
+    #[test]
+    fn expands_known_env_vars() {
+        std::env::set_var("OXIDEDOCK_TEST_HOME", r"C:\Dock");
+        assert_eq!(
+            expand_env_vars(r"%OXIDEDOCK_TEST_HOME%\bin"),
+            r"C:\Dock\bin"
+        );
+    }
+
+    #[test]
+    fn keeps_percent_signs_around_empty_names() {
+        std::env::set_var("OXIDEDOCK_TEST_AFTER_EMPTY", "yes");
+        assert_eq!(expand_env_vars("%%"), "%%");
+        assert_eq!(expand_env_vars("a%%b"), "a%%b");
+        assert_eq!(expand_env_vars("%%OXIDEDOCK_TEST_AFTER_EMPTY%"), "%yes");
+    }
+
+    #[test]
+    fn keeps_unterminated_percent_signs() {
+        assert_eq!(expand_env_vars("100%"), "100%");
+        assert_eq!(
+            expand_env_vars("%OXIDEDOCK_TEST_OPEN"),
+            "%OXIDEDOCK_TEST_OPEN"
+        );
+    }
+
+    #[test]
+    fn expands_known_name_after_unknown_one() {
+        std::env::set_var("OXIDEDOCK_TEST_KNOWN", "yes");
+        assert_eq!(
+            expand_env_vars("%OXIDEDOCK_TEST_UNSET% %OXIDEDOCK_TEST_KNOWN%"),
+            "%OXIDEDOCK_TEST_UNSET% yes"
+        );
+    }
+
+    #[test]
+    fn style_override_sets_and_keeps_reflections() {
+        let style = DockStyle {
+            reflections: true,
+            ..DockStyle::default()
+        };
+        assert!(PartialDockStyle::default().apply(&style).reflections);
+
+        let mut overrides = PartialDockStyle {
+            reflections: Some(false),
+            ..PartialDockStyle::default()
+        };
+        assert!(!overrides.apply(&style).reflections);
+
+        // A later override that leaves reflections unset keeps the earlier one
+        overrides.merge(PartialDockStyle {
+            tilt_effect: Some(true),
+            ..PartialDockStyle::default()
+        });
+        let applied = overrides.apply(&style);
+        assert!(!applied.reflections);
+        assert!(applied.tilt_effect);
+    }
+
+    #[test]
+    fn reflections_default_off_and_are_omitted_when_off() {
+        let style: DockStyle = serde_json::from_str("{}").unwrap();
+        assert!(!style.reflections);
+        let json = serde_json::to_value(&style).unwrap();
+        assert!(json.get("reflections").is_none());
+    }
+}
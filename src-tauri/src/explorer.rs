@@ -0,0 +1,98 @@
+use log::{info, warn};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use tauri::Manager;
+use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
+use windows::Win32::UI::Shell::{DefSubclassProc, SetWindowSubclass};
+use windows::Win32::UI::WindowsAndMessaging::{
+    RegisterWindowMessageW, SPI_SETWORKAREA, WA_INACTIVE, WM_ACTIVATE, WM_DISPLAYCHANGE,
+    WM_SETTINGCHANGE,
+};
+use windows::core::w;
+
+/// Identifies our subclass on the dock window.
+const SUBCLASS_ID: usize = 0x0D0C;
+
+/// The registered "TaskbarCreated" message, broadcast when Explorer (re)starts.
+static TASKBAR_CREATED: OnceLock<u32> = OnceLock::new();
+
+/// Bumped by each shell change; only the last one in a burst reapplies.
+static REAPPLY_GEN: AtomicU64 = AtomicU64::new(0);
+
+/// Some change in the pending burst needs a forced reapply.
+static REAPPLY_FORCE: AtomicBool = AtomicBool::new(false);
+
+/// Re-apply the taskbar hiding and AppBar reservation whenever Explorer
+/// restarts, since the new taskbar starts out visible and the old AppBar
+/// registration is gone. Display and work-area changes (resolution, another
+/// AppBar, the taskbar coming back) re-run positioning too.
+pub fn watch_shell_changes(window: &tauri::WebviewWindow) {
+    let Ok(hwnd_raw) = window.hwnd() else {
+        return;
+    };
+    let message = unsafe { RegisterWindowMessageW(w!("TaskbarCreated")) };
+    if message == 0 {
+        warn!("Explorer restarts won't be noticed: RegisterWindowMessageW failed");
+    } else {
+        let _ = TASKBAR_CREATED.set(message);
+    }
+
+    // Owned by the subclass for the lifetime of the window
+    let app = Box::into_raw(Box::new(window.app_handle().clone()));
+    unsafe {
+        if !SetWindowSubclass(
+            HWND(hwnd_raw.0 as isize),
+            Some(subclass_proc),
+            SUBCLASS_ID,
+            app as usize,
+        )
+        .as_bool()
+        {
+            warn!("Shell changes won't be noticed: SetWindowSubclass failed");
+            drop(Box::from_raw(app));
+        }
+    }
+}
+
+unsafe extern "system" fn subclass_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+    _id: usize,
+    ref_data: usize,
+) -> LRESULT {
+    // lParam is the window losing focus to the dock (click_focused_action)
+    if msg == WM_ACTIVATE && (wparam.0 & 0xFFFF) as u32 != WA_INACTIVE {
+        crate::process::note_activated_from(HWND(lparam.0));
+    }
+
+    let reapply = if TASKBAR_CREATED.get() == Some(&msg) {
+        info!("Explorer restarted, restoring dock environment");
+        Some(true)
+    } else if msg == WM_DISPLAYCHANGE {
+        info!("Display settings changed, repositioning the dock");
+        Some(true)
+    } else if msg == WM_SETTINGCHANGE && wparam.0 == SPI_SETWORKAREA.0 as usize {
+        // Also sent for our own AppBar updates; not forcing keeps those no-ops
+        Some(false)
+    } else {
+        None
+    };
+
+    if let Some(force) = reapply {
+        let app = (*(ref_data as *const tauri::AppHandle)).clone();
+        REAPPLY_FORCE.fetch_or(force, Ordering::SeqCst);
+        let generation = REAPPLY_GEN.fetch_add(1, Ordering::SeqCst) + 1;
+        // Off the window thread: repositioning sends messages back to it.
+        // Only the last change in a burst survives the wait and reapplies.
+        std::thread::spawn(move || {
+            std::thread::sleep(crate::REPOSITION_DEBOUNCE);
+            if REAPPLY_GEN.load(Ordering::SeqCst) == generation {
+                let force = REAPPLY_FORCE.swap(false, Ordering::SeqCst);
+                crate::reapply_shell_state(&app, force);
+            }
+        });
+    }
+    DefSubclassProc(hwnd, msg, wparam, lparam)
+}
@@ -0,0 +1,97 @@
+use crate::AppState;
+use log::info;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::Manager;
+
+#[cfg(target_os = "windows")]
+use windows::Win32::{
+    Foundation::{HWND, RECT},
+    Graphics::Gdi::{GetMonitorInfoW, MONITOR_DEFAULTTONEAREST, MONITORINFO, MonitorFromWindow},
+    UI::WindowsAndMessaging::{
+        GetClassNameW, GetDesktopWindow, GetForegroundWindow, GetShellWindow, GetWindowRect,
+        GetWindowThreadProcessId,
+    },
+};
+
+/// How often the foreground window is checked for fullscreen.
+const POLL_INTERVAL: Duration = Duration::from_millis(1000);
+
+/// Hide the dock (and drop topmost) while a fullscreen app owns the
+/// foreground, bringing it back once focus returns to a normal window.
+pub fn watch(window: tauri::WebviewWindow) {
+    std::thread::spawn(move || {
+        let mut yielded = false;
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+
+            let fullscreen = is_fullscreen_app_active();
+            if fullscreen == yielded {
+                continue;
+            }
+            yielded = fullscreen;
+
+            if fullscreen {
+                info!("Fullscreen app detected, hiding dock");
+                let _ = window.set_always_on_top(false);
+                let _ = window.hide();
+            } else {
+                info!("Fullscreen app gone, restoring dock");
+                let _ = window.show();
+                let topmost = window
+                    .state::<Mutex<AppState>>()
+                    .lock()
+                    .map(|s| s.config.topmost)
+                    .unwrap_or(true);
+                let _ = window.set_always_on_top(topmost);
+            }
+        }
+    });
+}
+
+/// Whether the foreground window covers its whole monitor. The desktop and
+/// shell windows (which are "fullscreen" too) and the dock itself don't count.
+#[cfg(target_os = "windows")]
+fn is_fullscreen_app_active() -> bool {
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd == HWND::default() || hwnd == GetDesktopWindow() || hwnd == GetShellWindow() {
+            return false;
+        }
+
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid));
+        if pid == std::process::id() {
+            return false;
+        }
+
+        let mut class = [0u16; 64];
+        let len = GetClassNameW(hwnd, &mut class).max(0) as usize;
+        let class = String::from_utf16_lossy(&class[..len]);
+        if class == "Progman" || class == "WorkerW" {
+            return false;
+        }
+
+        let mut rect = RECT::default();
+        if GetWindowRect(hwnd, &mut rect).is_err() {
+            return false;
+        }
+
+        let monitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
+        let mut info = MONITORINFO {
+            cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+            ..Default::default()
+        };
+        if !GetMonitorInfoW(monitor, &mut info).as_bool() {
+            return false;
+        }
+
+        let m = info.rcMonitor;
+        rect.left <= m.left && rect.top <= m.top && rect.right >= m.right && rect.bottom >= m.bottom
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn is_fullscreen_app_active() -> bool {
+    false
+}
@@ -0,0 +1,125 @@
+use crate::AppState;
+use crate::config::HotkeyConfig;
+use log::{info, warn};
+use std::sync::Mutex;
+use tauri::{Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+
+/// Dock actions that can be bound to a global key combo.
+#[derive(Debug, Clone, Copy)]
+enum HotkeyAction {
+    Reload,
+    ToggleHidden,
+    Quit,
+    OpenSettings,
+    PinForeground,
+    ToggleLabels,
+    ReloadIcons,
+}
+
+/// Register the configured combos (e.g. `"Ctrl+Alt+D"`). A combo that fails
+/// to parse or is already taken by another app is logged and skipped.
+pub fn register(app: &tauri::AppHandle, hotkeys: &HotkeyConfig) {
+    let bindings = [
+        (&hotkeys.reload, HotkeyAction::Reload),
+        (&hotkeys.toggle_hidden, HotkeyAction::ToggleHidden),
+        (&hotkeys.quit, HotkeyAction::Quit),
+        (&hotkeys.open_settings, HotkeyAction::OpenSettings),
+        (&hotkeys.pin_foreground, HotkeyAction::PinForeground),
+        (&hotkeys.toggle_labels, HotkeyAction::ToggleLabels),
+        (&hotkeys.reload_icons, HotkeyAction::ReloadIcons),
+    ];
+
+    for (combo, action) in bindings {
+        let Some(combo) = combo.as_deref() else {
+            continue;
+        };
+        let result = app
+            .global_shortcut()
+            .on_shortcut(combo, move |app, _shortcut, event| {
+                if event.state == ShortcutState::Pressed {
+                    run(app, action);
+                }
+            });
+        match result {
+            Ok(()) => info!("Hotkey {} bound to {:?}", combo, action),
+            Err(e) => warn!("Hotkey {} for {:?} not registered: {}", combo, action, e),
+        }
+    }
+}
+
+fn run(app: &tauri::AppHandle, action: HotkeyAction) {
+    match action {
+        HotkeyAction::Reload => {
+            let state = app.state::<Mutex<AppState>>();
+            let Ok(config_path) = state.lock().map(|s| s.config_path.clone()) else {
+                return;
+            };
+            // Loaded outside the lock: discovery does COM and folder scans
+            let config = match crate::config::load_config(&config_path) {
+                Ok(config) => config,
+                Err(e) => {
+                    warn!("Failed to reload config: {}", e);
+                    return;
+                }
+            };
+            if let Ok(mut state) = state.lock() {
+                state.config = config;
+                state.config_error = None;
+            }
+            info!("Config reloaded from {:?}", config_path);
+            crate::apply_topmost(app, &state);
+            let _ = app.emit_to("main", "config-changed", ());
+        }
+        HotkeyAction::ToggleHidden => {
+            let Some(window) = app.get_webview_window("main") else {
+                return;
+            };
+            let state = app.state::<Mutex<AppState>>();
+            let hidden = match state.lock() {
+                Ok(mut state) => {
+                    state.is_hidden = !state.is_hidden;
+                    state.is_hidden
+                }
+                Err(_) => return,
+            };
+            crate::update_dock_position(&window, &state);
+            // Keep the frontend's auto-hide bookkeeping in sync
+            let _ = app.emit_to("main", "dock-hidden-changed", hidden);
+        }
+        HotkeyAction::Quit => {
+            info!("Quit requested by hotkey");
+            crate::quit(app);
+        }
+        HotkeyAction::OpenSettings => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = crate::open_settings(app).await {
+                    warn!("Failed to open settings: {}", e);
+                }
+            });
+        }
+        HotkeyAction::PinForeground => {
+            let state = app.state::<Mutex<AppState>>();
+            match crate::pin_foreground(app, &state) {
+                Ok(Some(name)) => info!("Pinned {} by hotkey", name),
+                Ok(None) => info!("Foreground app is already pinned"),
+                Err(e) => warn!("Failed to pin foreground app: {}", e),
+            }
+        }
+        HotkeyAction::ToggleLabels => {
+            let state = app.state::<Mutex<AppState>>();
+            let Ok(mode) = state.lock().map(|s| s.style().label_mode.toggled()) else {
+                return;
+            };
+            if let Err(e) = crate::apply_label_mode(app, &state, mode) {
+                warn!("Failed to switch label mode: {}", e);
+            }
+        }
+        HotkeyAction::ReloadIcons => {
+            if let Err(e) = crate::reload_icons(app.clone()) {
+                warn!("Failed to reload icons: {}", e);
+            }
+        }
+    }
+}
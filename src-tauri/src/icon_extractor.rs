@@ -1,71 +1,274 @@
-use image::RgbaImage;
+use image::codecs::{gif::GifDecoder, png::PngDecoder};
+use image::{AnimationDecoder, RgbaImage};
+use log::{debug, warn};
+use serde::Serialize;
 use std::ffi::OsStr;
 use std::os::windows::ffi::OsStrExt;
 use std::path::Path;
-use windows::Win32::Foundation::SIZE;
+use windows::Win32::Foundation::{E_FAIL, HANDLE, SIZE};
 use windows::Win32::Graphics::Gdi::{
     BI_RGB, BITMAPINFO, BITMAPINFOHEADER, CreateCompatibleDC, DIB_RGB_COLORS, DeleteDC,
     DeleteObject, GetDIBits, SelectObject,
 };
+use windows::Win32::Storage::FileSystem::{
+    FILE_ATTRIBUTE_DIRECTORY, FILE_ATTRIBUTE_NORMAL, FILE_FLAGS_AND_ATTRIBUTES,
+};
+use windows::Win32::System::Com::CoTaskMemFree;
 use windows::Win32::UI::Controls::IImageList;
 use windows::Win32::UI::Shell::{
-    SHFILEINFOW, SHGFI_SYSICONINDEX, SHGetFileInfoW, SHGetImageList, SHIL_EXTRALARGE, SHIL_JUMBO,
+    ExtractIconExW, FOLDERID_Desktop, FOLDERID_Documents, FOLDERID_Downloads, FOLDERID_Music,
+    FOLDERID_Pictures, FOLDERID_Videos, KF_FLAG_DEFAULT, SHFILEINFOW, SHGFI_PIDL,
+    SHGFI_SYSICONINDEX, SHGFI_USEFILEATTRIBUTES, SHGetFileInfoW, SHGetImageList,
+    SHGetKnownFolderIDList, SHGetKnownFolderPath, SHIL_EXTRALARGE, SHIL_JUMBO, SHIL_LARGE,
+    SHIL_SMALL,
 };
-use windows::Win32::UI::WindowsAndMessaging::DestroyIcon;
-use windows::core::PCWSTR;
+use windows::Win32::UI::WindowsAndMessaging::{DestroyIcon, GetSystemMetrics, HICON, SM_CXICON};
+use windows::core::{GUID, HRESULT, PCWSTR, w};
+
+/// Why no icon could be extracted for a path.
+#[derive(Debug)]
+pub enum IconError {
+    NotFound,
+    /// The shell/GDI calls behind every method failed; the last failure
+    ShellFailed(HRESULT),
+    /// Extraction worked but every icon found was fully transparent
+    Blank,
+    /// A custom image file couldn't be decoded, and no shell icon worked either
+    DecodeFailed(String),
+    /// Extraction panicked (e.g. on a malformed icon resource)
+    Panicked,
+}
+
+impl std::fmt::Display for IconError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IconError::NotFound => write!(f, "file does not exist"),
+            IconError::ShellFailed(hr) => {
+                write!(f, "shell icon extraction failed (0x{:08X})", hr.0)
+            }
+            IconError::Blank => write!(f, "icon is blank"),
+            IconError::DecodeFailed(e) => write!(f, "image could not be decoded: {}", e),
+            IconError::Panicked => write!(f, "icon extraction crashed"),
+        }
+    }
+}
+
+impl std::error::Error for IconError {}
+
+impl From<windows::core::Error> for IconError {
+    fn from(e: windows::core::Error) -> Self {
+        IconError::ShellFailed(e.code())
+    }
+}
 
 /// Extract the highest-resolution icon for a given file path.
 /// Uses SHGetImageList(SHIL_JUMBO) to get 256×256 icons on modern Windows,
-/// falling back to SHIL_EXTRALARGE (48×48) and then to windows-icons crate.
-pub fn extract_icon(path: &str) -> Option<RgbaImage> {
+/// falling back to SHIL_EXTRALARGE (48×48), then ExtractIconExW on the file's
+/// own icon resources, and finally the windows-icons crate.
+///
+/// An explicit `icon_index` selects an embedded icon of a multi-icon
+/// .exe/.dll, so it goes straight to ExtractIconExW. Image files (custom
+/// icons) are decoded and used as-is.
+///
+/// When every method fails, the most telling failure is returned: a broken
+/// custom image over a blank icon over a failed shell call.
+///
+/// A panic during extraction is contained and reported as
+/// `IconError::Panicked`, so one bad file costs its icon rather than the dock.
+pub fn extract_icon(path: &str, icon_index: Option<i32>) -> Result<RgbaImage, IconError> {
+    // Nothing shared is left half-updated: every handle/buffer is local
+    std::panic::catch_unwind(|| extract_icon_unguarded(path, icon_index)).unwrap_or_else(|_| {
+        warn!("Icon extraction panicked for '{}'", path);
+        Err(IconError::Panicked)
+    })
+}
+
+fn extract_icon_unguarded(path: &str, icon_index: Option<i32>) -> Result<RgbaImage, IconError> {
     // Verify path exists
     if !Path::new(path).exists() {
-        println!("  Icon FAIL: '{}' -> file does not exist", path);
-        return None;
+        debug!("Icon FAIL: '{}' -> file does not exist", path);
+        return Err(IconError::NotFound);
+    }
+
+    let mut decode_error = None;
+    if is_image_file(path) {
+        match image::open(path) {
+            Ok(img) => {
+                debug!("Icon OK: '{}' [IMAGE]", path);
+                return Ok(img.to_rgba8());
+            }
+            Err(e) => {
+                debug!("Icon image decode failed: '{}' -> {}", path, e);
+                decode_error = Some(IconError::DecodeFailed(e.to_string()));
+            }
+        }
+    }
+
+    let mut error = IconError::ShellFailed(E_FAIL);
+    let mut note = |e: IconError| {
+        // A blank result says more than a later method failing outright
+        if !matches!(error, IconError::Blank) {
+            error = e;
+        }
+    };
+
+    if let Some(index) = icon_index {
+        match extract_resource_icon(path, index) {
+            Ok(img) => {
+                debug!("Icon OK: '{}' #{} [RESOURCE]", path, index);
+                return Ok(img);
+            }
+            Err(e) => note(e),
+        }
     }
 
     // Try JUMBO first (256x256), then EXTRALARGE (48x48)
-    if let Some(img) = extract_shell_icon(path, SHIL_JUMBO as i32) {
-        println!(
-            "  Icon OK: '{}' ({}x{}) [JUMBO]",
-            path,
-            img.width(),
-            img.height()
-        );
-        return Some(img);
+    match extract_shell_icon(path, SHIL_JUMBO as i32) {
+        Ok(img) => {
+            debug!(
+                "Icon OK: '{}' ({}x{}) [JUMBO]",
+                path,
+                img.width(),
+                img.height()
+            );
+            return Ok(img);
+        }
+        Err(e) => note(e),
     }
 
-    if let Some(img) = extract_shell_icon(path, SHIL_EXTRALARGE as i32) {
-        println!(
-            "  Icon OK: '{}' ({}x{}) [EXTRALARGE]",
-            path,
-            img.width(),
-            img.height()
-        );
-        return Some(img);
+    match extract_shell_icon(path, SHIL_EXTRALARGE as i32) {
+        Ok(img) => {
+            debug!(
+                "Icon OK: '{}' ({}x{}) [EXTRALARGE]",
+                path,
+                img.width(),
+                img.height()
+            );
+            return Ok(img);
+        }
+        Err(e) => note(e),
+    }
+
+    if icon_index.is_none() {
+        match extract_resource_icon(path, 0) {
+            Ok(img) => {
+                debug!("Icon OK: '{}' [RESOURCE]", path);
+                return Ok(img);
+            }
+            Err(e) => note(e),
+        }
     }
 
     // Final fallback: windows-icons crate
     match windows_icons::get_icon_by_path(path) {
         Ok(icon) => {
             let (w, h) = (icon.width(), icon.height());
-            println!("  Icon OK: '{}' ({}x{}) [fallback]", path, w, h);
+            debug!("Icon OK: '{}' ({}x{}) [fallback]", path, w, h);
             if w < 48 {
-                Some(image::imageops::resize(
+                Ok(image::imageops::resize(
                     &icon,
                     128,
                     128,
                     image::imageops::FilterType::Lanczos3,
                 ))
             } else {
-                Some(icon)
+                Ok(icon)
             }
         }
         Err(e) => {
-            println!("  Icon FAIL: '{}' -> {}", path, e);
-            None
+            debug!("Icon FAIL: '{}' -> {}", path, e);
+            Err(decode_error.unwrap_or(error))
+        }
+    }
+}
+
+/// Animations with more frames than this are shown as a still image.
+const MAX_ANIMATION_FRAMES: usize = 256;
+/// Larger animated files are shown as a still image.
+const MAX_ANIMATION_BYTES: u64 = 4 * 1024 * 1024;
+/// Frames shorter than this would have the webview repaint constantly.
+const MIN_FRAME_DELAY_MS: u32 = 20;
+
+/// MIME type and raw bytes of an animated GIF/APNG custom icon, for the
+/// webview to play natively. `None` for still images and for animations too
+/// large or too fast to be worth the CPU.
+pub fn animated_image(path: &str) -> Option<(&'static str, Vec<u8>)> {
+    let ext = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+    if ext != "gif" && ext != "png" {
+        return None;
+    }
+    if std::fs::metadata(path).ok()?.len() > MAX_ANIMATION_BYTES {
+        debug!("Icon animation too large, showing still: '{}'", path);
+        return None;
+    }
+    let bytes = std::fs::read(path).ok()?;
+    let reader = std::io::Cursor::new(&bytes);
+
+    let frames = if ext == "gif" {
+        GifDecoder::new(reader).ok()?.into_frames()
+    } else {
+        let decoder = PngDecoder::new(reader).ok()?;
+        if !decoder.is_apng().ok()? {
+            return None;
+        }
+        decoder.apng().ok()?.into_frames()
+    };
+
+    let mut count = 0;
+    for frame in frames.take(MAX_ANIMATION_FRAMES + 1) {
+        let (numer, denom) = frame.ok()?.delay().numer_denom_ms();
+        if numer < MIN_FRAME_DELAY_MS * denom.max(1) {
+            debug!("Icon animation too fast, showing still: '{}'", path);
+            return None;
         }
+        count += 1;
     }
+    if !(2..=MAX_ANIMATION_FRAMES).contains(&count) {
+        return None;
+    }
+
+    let mime = if ext == "gif" {
+        "image/gif"
+    } else {
+        "image/png"
+    };
+    Some((mime, bytes))
+}
+
+/// Larger SVG files are assumed not to be icons.
+const MAX_SVG_BYTES: u64 = 1024 * 1024;
+
+/// Raw markup of an SVG custom icon. The webview rasterizes it at the exact
+/// device pixel size it's drawn at, again after every DPI or magnification
+/// change, so vector icons never go soft from scaling a fixed bitmap.
+pub fn svg_image(path: &str) -> Option<Vec<u8>> {
+    let ext = Path::new(path).extension()?.to_str()?;
+    if !ext.eq_ignore_ascii_case("svg") {
+        return None;
+    }
+    if std::fs::metadata(path).ok()?.len() > MAX_SVG_BYTES {
+        debug!("Icon SVG too large: '{}'", path);
+        return None;
+    }
+    let bytes = std::fs::read(path).ok()?;
+    // Cheap sanity check so a mislabelled file falls back to the shell icon
+    if !String::from_utf8_lossy(&bytes).contains("<svg") {
+        debug!("Icon SVG has no <svg> element: '{}'", path);
+        return None;
+    }
+    debug!("Icon OK: '{}' [SVG]", path);
+    Some(bytes)
+}
+
+fn is_image_file(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .and_then(|s| s.to_str())
+        .is_some_and(|ext| {
+            ["png", "jpg", "jpeg", "bmp", "gif", "webp"]
+                .iter()
+                .any(|e| ext.eq_ignore_ascii_case(e))
+        })
 }
 
 fn to_wide(s: &str) -> Vec<u16> {
@@ -75,40 +278,147 @@ fn to_wide(s: &str) -> Vec<u16> {
         .collect()
 }
 
-fn extract_shell_icon(path: &str, image_list_type: i32) -> Option<RgbaImage> {
+/// One system image list size and whether it produced a usable icon.
+#[derive(Debug, Serialize)]
+pub struct IconTierInfo {
+    pub tier: &'static str,
+    pub width: u32,
+    pub height: u32,
+    pub has_content: bool,
+}
+
+/// Probe every system image list tier for `path`, largest first, to diagnose
+/// low-resolution icons.
+pub fn icon_tiers(path: &str) -> Vec<IconTierInfo> {
+    let tiers = [
+        ("jumbo", SHIL_JUMBO),
+        ("extralarge", SHIL_EXTRALARGE),
+        ("large", SHIL_LARGE),
+        ("small", SHIL_SMALL),
+    ];
+
+    tiers
+        .into_iter()
+        .filter_map(|(tier, list)| {
+            let (width, height) = image_list_icon_size(list as i32)?;
+            Some(IconTierInfo {
+                tier,
+                width,
+                height,
+                has_content: extract_shell_icon(path, list as i32).is_ok(),
+            })
+        })
+        .collect()
+}
+
+fn image_list_icon_size(image_list_type: i32) -> Option<(u32, u32)> {
     unsafe {
-        // Get the icon index in the system image list
-        let wide_path = to_wide(path);
-        let mut shfi = SHFILEINFOW::default();
+        let image_list: IImageList = SHGetImageList(image_list_type).ok()?;
+        let mut size = SIZE::default();
+        image_list.GetIconSize(&mut size.cx, &mut size.cy).ok()?;
+        Some((size.cx as u32, size.cy as u32))
+    }
+}
+
+/// Folders with their own shell icon, matched by path.
+const KNOWN_FOLDERS: [GUID; 6] = [
+    FOLDERID_Downloads,
+    FOLDERID_Documents,
+    FOLDERID_Desktop,
+    FOLDERID_Pictures,
+    FOLDERID_Music,
+    FOLDERID_Videos,
+];
+
+/// Index of `path`'s icon in the system image list. Well-known folders are
+/// looked up through their shell item so they keep their distinct icon, and a
+/// directory the plain query can't resolve gets the generic folder icon.
+unsafe fn shell_icon_index(path: &str) -> Option<i32> {
+    let is_dir = Path::new(path).is_dir();
+    if is_dir {
+        if let Some(index) = known_folder_icon_index(path) {
+            return Some(index);
+        }
+    }
+
+    let wide_path = to_wide(path);
+    let mut shfi = SHFILEINFOW::default();
+    let result = SHGetFileInfoW(
+        PCWSTR(wide_path.as_ptr()),
+        FILE_ATTRIBUTE_NORMAL,
+        Some(&mut shfi),
+        std::mem::size_of::<SHFILEINFOW>() as u32,
+        SHGFI_SYSICONINDEX,
+    );
+    if result != 0 {
+        return Some(shfi.iIcon);
+    }
+
+    if is_dir {
+        // Attributes alone: the name is never touched
         let result = SHGetFileInfoW(
-            PCWSTR(wide_path.as_ptr()),
-            windows::Win32::Storage::FileSystem::FILE_ATTRIBUTE_NORMAL,
+            w!("folder"),
+            FILE_ATTRIBUTE_DIRECTORY,
             Some(&mut shfi),
             std::mem::size_of::<SHFILEINFOW>() as u32,
-            SHGFI_SYSICONINDEX,
+            SHGFI_SYSICONINDEX | SHGFI_USEFILEATTRIBUTES,
         );
+        if result != 0 {
+            return Some(shfi.iIcon);
+        }
+    }
+    None
+}
 
-        if result == 0 {
-            return None;
+unsafe fn known_folder_icon_index(path: &str) -> Option<i32> {
+    let normalize = |p: &str| p.trim_end_matches(['\\', '/']).to_lowercase();
+    let target = normalize(path);
+
+    for id in &KNOWN_FOLDERS {
+        let Ok(folder) = SHGetKnownFolderPath(id, KF_FLAG_DEFAULT, HANDLE::default()) else {
+            continue;
+        };
+        let folder_path = folder.to_string().unwrap_or_default();
+        CoTaskMemFree(Some(folder.0 as *const _));
+        if normalize(&folder_path) != target {
+            continue;
         }
 
-        let icon_index = shfi.iIcon;
+        let pidl = SHGetKnownFolderIDList(id, 0, HANDLE::default()).ok()?;
+        let mut shfi = SHFILEINFOW::default();
+        let result = SHGetFileInfoW(
+            PCWSTR(pidl as *const u16),
+            FILE_FLAGS_AND_ATTRIBUTES(0),
+            Some(&mut shfi),
+            std::mem::size_of::<SHFILEINFOW>() as u32,
+            SHGFI_PIDL | SHGFI_SYSICONINDEX,
+        );
+        CoTaskMemFree(Some(pidl as *const _));
+        return (result != 0).then_some(shfi.iIcon);
+    }
+    None
+}
+
+fn extract_shell_icon(path: &str, image_list_type: i32) -> Result<RgbaImage, IconError> {
+    unsafe {
+        // Get the icon index in the system image list
+        let icon_index = shell_icon_index(path).ok_or(IconError::ShellFailed(E_FAIL))?;
 
         // Get the image list for the requested size
-        let image_list: IImageList = SHGetImageList(image_list_type).ok()?;
+        let image_list: IImageList = SHGetImageList(image_list_type)?;
 
         // Get the icon size
         let mut size = SIZE::default();
-        image_list.GetIconSize(&mut size.cx, &mut size.cy).ok()?;
+        image_list.GetIconSize(&mut size.cx, &mut size.cy)?;
         let icon_w = size.cx as u32;
         let icon_h = size.cy as u32;
 
         if icon_w == 0 || icon_h == 0 {
-            return None;
+            return Err(IconError::ShellFailed(E_FAIL));
         }
 
         // Extract HICON
-        let hicon = image_list.GetIcon(icon_index, 0).ok()?;
+        let hicon = image_list.GetIcon(icon_index, 0)?;
 
         // Convert HICON to RGBA pixels
         let img = hicon_to_rgba(hicon, icon_w, icon_h);
@@ -120,19 +430,44 @@ fn extract_shell_icon(path: &str, image_list_type: i32) -> Option<RgbaImage> {
     }
 }
 
+/// Pull the large icon at `index` straight from an .exe/.dll's icon resources.
+fn extract_resource_icon(path: &str, index: i32) -> Result<RgbaImage, IconError> {
+    unsafe {
+        let wide_path = to_wide(path);
+        let mut hicon = HICON::default();
+        let extracted =
+            ExtractIconExW(PCWSTR(wide_path.as_ptr()), index, Some(&mut hicon), None, 1);
+        if extracted == 0 || hicon.is_invalid() {
+            return Err(IconError::ShellFailed(E_FAIL));
+        }
+
+        let size = GetSystemMetrics(SM_CXICON).max(1) as u32;
+        let img = hicon_to_rgba(hicon, size, size);
+        let _ = DestroyIcon(hicon);
+
+        // Resource icons come at SM_CXICON (usually 32px); upscale like the
+        // windows-icons fallback so they aren't tiny next to jumbo icons
+        img.map(|img| {
+            if img.width() < 48 {
+                image::imageops::resize(&img, 128, 128, image::imageops::FilterType::Lanczos3)
+            } else {
+                img
+            }
+        })
+    }
+}
+
 unsafe fn hicon_to_rgba(
     hicon: windows::Win32::UI::WindowsAndMessaging::HICON,
     width: u32,
     height: u32,
-) -> Option<RgbaImage> {
+) -> Result<RgbaImage, IconError> {
     use windows::Win32::Graphics::Gdi::CreateDIBSection;
     use windows::Win32::UI::WindowsAndMessaging::{GetIconInfo, ICONINFO};
 
     // Get icon info to access the bitmaps
     let mut icon_info = ICONINFO::default();
-    if !GetIconInfo(hicon, &mut icon_info).is_ok() {
-        return None;
-    }
+    GetIconInfo(hicon, &mut icon_info)?;
 
     // Create a memory DC
     let hdc_screen =
@@ -159,7 +494,7 @@ unsafe fn hicon_to_rgba(
 
     // Create a DIB section to render the icon into
     let mut bits_ptr: *mut std::ffi::c_void = std::ptr::null_mut();
-    let hbm = CreateDIBSection(hdc, &bmi, DIB_RGB_COLORS, &mut bits_ptr, None, 0).ok()?;
+    let hbm = CreateDIBSection(hdc, &bmi, DIB_RGB_COLORS, &mut bits_ptr, None, 0)?;
 
     let old_bm = SelectObject(hdc, hbm);
 
@@ -174,8 +509,7 @@ unsafe fn hicon_to_rgba(
         0,
         None,
         windows::Win32::UI::WindowsAndMessaging::DI_NORMAL,
-    )
-    .ok()?;
+    )?;
 
     // Read the pixel data
     let pixel_count = (width * height) as usize;
@@ -213,13 +547,68 @@ unsafe fn hicon_to_rgba(
     }
 
     // Create image, skip if entirely transparent
-    let img = RgbaImage::from_raw(width, height, pixels)?;
+    let img = RgbaImage::from_raw(width, height, pixels).ok_or(IconError::ShellFailed(E_FAIL))?;
 
     // Check if image has any non-zero alpha (not blank)
     let has_content = img.pixels().any(|p| p.0[3] > 0);
     if has_content {
-        Some(img)
+        Ok(img)
     } else {
-        None // Blank icon, try next method
+        Err(IconError::Blank) // Try next method
+    }
+}
+
+#[cfg(all(test, windows))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_directory_icon() {
+        let dir = std::env::temp_dir();
+        let path = dir.to_str().expect("temp dir is valid UTF-8");
+        if let Err(e) = extract_icon(path, None) {
+            panic!("no icon for {:?}: {}", dir, e);
+        }
+    }
+
+    #[test]
+    fn extracts_icons_for_cjk_and_emoji_names() {
+        let dir = std::env::temp_dir().join(format!("oxidedock-icons-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("メモ帳 🚀 テスト.txt");
+        std::fs::write(&file, "").unwrap();
+
+        let path = file.to_str().unwrap();
+        // Both image list sizes, not just whichever extract_icon stops at
+        let jumbo = extract_shell_icon(path, SHIL_JUMBO as i32);
+        let extralarge = extract_shell_icon(path, SHIL_EXTRALARGE as i32);
+        let extracted = extract_icon(path, None);
+        let _ = std::fs::remove_dir_all(&dir);
+
+        if let Err(e) = jumbo {
+            panic!("no jumbo icon for {:?}: {}", file, e);
+        }
+        if let Err(e) = extralarge {
+            panic!("no extra-large icon for {:?}: {}", file, e);
+        }
+        if let Err(e) = extracted {
+            panic!("no icon for {:?}: {}", file, e);
+        }
+    }
+
+    #[test]
+    fn extracts_known_folder_icon() {
+        let path = unsafe {
+            let folder =
+                SHGetKnownFolderPath(&FOLDERID_Documents, KF_FLAG_DEFAULT, HANDLE::default())
+                    .expect("Documents folder");
+            let path = folder.to_string().expect("folder path");
+            CoTaskMemFree(Some(folder.0 as *const _));
+            path
+        };
+        assert!(unsafe { known_folder_icon_index(&path) }.is_some());
+        if let Err(e) = extract_icon(&path, None) {
+            panic!("no icon for '{}': {}", path, e);
+        }
     }
 }
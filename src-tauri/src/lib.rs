@@ -1,28 +1,733 @@
+mod accessibility;
+mod capture;
+mod clickthrough;
 mod config;
+mod explorer;
+mod fullscreen;
+mod hotkeys;
 mod icon_extractor;
+mod logging;
+mod model;
+mod network;
+mod occlusion;
+mod power;
+mod process;
+mod profile;
+mod seen;
+mod sound;
+mod transfer;
+mod usage;
 
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
 use image::ImageEncoder;
-use std::sync::Mutex;
-use tauri::{Manager, State, WindowEvent};
+use log::{debug, error, info, warn};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{Emitter, Manager, State, WindowEvent};
+use tauri_plugin_opener::OpenerExt;
 use windows::Win32::Foundation::{HWND, RECT};
+use windows::Win32::Graphics::Gdi::{MONITOR_DEFAULTTONEAREST, MonitorFromWindow};
 use windows::Win32::UI::Shell::{
-    ABE_BOTTOM, ABM_NEW, ABM_REMOVE, ABM_SETPOS, APPBARDATA, SHAppBarMessage,
+    ABE_BOTTOM, ABM_NEW, ABM_REMOVE, ABM_SETPOS, APPBARDATA, SHAppBarMessage, ShellExecuteW,
 };
-use windows::Win32::UI::WindowsAndMessaging::{FindWindowW, SW_HIDE, SW_SHOW, ShowWindow};
-use windows::core::w;
+use windows::Win32::UI::WindowsAndMessaging::{
+    FindWindowExW, FindWindowW, IDYES, MB_ICONERROR, MB_ICONQUESTION, MB_OK, MB_TOPMOST, MB_YESNO,
+    MessageBoxW, SW_HIDE, SW_SHOW, SW_SHOWNORMAL, ShowWindow,
+};
+use windows::core::{PCWSTR, w};
 
 pub struct AppState {
     pub config: config::Config,
+    pub config_path: std::path::PathBuf,
+    /// Why the config file couldn't be used, if it couldn't
+    pub config_error: Option<String>,
     pub is_hidden: bool,
+    /// Last geometry applied by `update_dock_position`, to skip no-op updates
+    pub dock_geometry: Option<DockGeometry>,
+    /// Minimal mode is in effect: taskbar shown, no AppBar reservation
+    pub minimal: bool,
+    /// Started with `--safe`: no shell integration at all, a static dock
+    pub safe_mode: bool,
+    /// Height the AppBar currently reserves, in physical pixels
+    pub appbar_height: Option<i32>,
+    /// Launch counts for frequency-sorted categories
+    pub usage: usage::UsageStore,
+    /// Shortcuts clicked at least once; the rest are badged as new
+    pub seen: seen::SeenStore,
+    /// Last process scan and when it was taken, reused by `get_running_apps`
+    pub running_cache: Option<(Instant, Vec<String>)>,
+    /// Unsaved style tweaks from `set_style_override`
+    pub style_override: config::PartialDockStyle,
+    /// Do not disturb: attention-grabbing feedback is suppressed
+    pub dnd: bool,
+    /// Where the bar sits in the window, reported by the frontend
+    pub bar_rect: Option<clickthrough::BarRect>,
+}
+
+impl AppState {
+    /// The config's style with any runtime override applied.
+    pub fn style(&self) -> config::DockStyle {
+        self.style_override.apply(&self.config.style)
+    }
 }
 
+/// Dock window placement in physical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct DockGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Where the dock actually is, for integrations and checking that the AppBar
+/// reservation matches the visible bar.
+#[derive(Debug, serde::Serialize)]
+struct DockBounds {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    monitor: Option<String>,
+    edge: &'static str,
+    hidden: bool,
+    appbar_height: Option<i32>,
+}
+
+/// A connected display, for choosing the `monitor` the dock is pinned to.
+#[derive(Debug, serde::Serialize)]
+struct MonitorInfo {
+    /// Device name, as matched by the `monitor` config field
+    name: Option<String>,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    scale: f64,
+    is_primary: bool,
+}
+
+/// Longest an icon can be kept highlighted by `highlight_icon`.
+const MAX_HIGHLIGHT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, serde::Serialize)]
+struct HighlightRequest {
+    path: String,
+    duration_ms: u32,
+}
+
+/// Quiet period before a burst of move/resize/DPI events is acted on.
+const REPOSITION_DEBOUNCE: Duration = Duration::from_millis(100);
+
 #[tauri::command]
 fn get_config(state: State<'_, Mutex<AppState>>) -> Result<serde_json::Value, String> {
     let state = state.lock().map_err(|e| e.to_string())?;
     serde_json::to_value(&state.config).map_err(|e| e.to_string())
 }
 
+/// The dock's view model: enabled categories in display order, shortcuts
+/// annotated with running state, target existence and a stable id.
+#[tauri::command]
+fn get_dock_model(state: State<'_, Mutex<AppState>>) -> Result<model::DockModel, String> {
+    // Snapshot under the lock; the process scan happens outside it
+    let (config, counts, new_keys) = {
+        let state = state.lock().map_err(|e| e.to_string())?;
+        let new_keys = state.seen.new_keys(&state.config);
+        (state.config.clone(), state.usage.counts().clone(), new_keys)
+    };
+    let running = without_ignored(process::running_app_paths(), &config.running_ignore);
+    Ok(model::build(&config, &counts, &running, &new_keys))
+}
+
+/// Persist an edited config (from the settings window) and reload it so
+/// discovered items are re-applied, then tell the dock to rebuild.
+#[tauri::command]
+fn save_config(
+    app: tauri::AppHandle,
+    state: State<'_, Mutex<AppState>>,
+    config: config::Config,
+) -> Result<(), String> {
+    replace_config(&app, &state, config)
+}
+
+/// The config as it would be written to disk, for backup/sharing.
+#[tauri::command]
+fn export_config(state: State<'_, Mutex<AppState>>) -> Result<String, String> {
+    let state = state.lock().map_err(|e| e.to_string())?;
+    config::to_json(&state.config).map_err(|e| e.to_string())
+}
+
+/// Replace the whole config from exported JSON. Invalid input is rejected
+/// before anything is touched.
+#[tauri::command]
+fn import_config(
+    app: tauri::AppHandle,
+    state: State<'_, Mutex<AppState>>,
+    json: String,
+) -> Result<(), String> {
+    let config: config::Config =
+        serde_json::from_str(&json).map_err(|e| format!("Invalid config JSON: {}", e))?;
+    replace_config(&app, &state, config)
+}
+
+/// A display name for `rename_*`: trimmed, and not empty.
+fn validate_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Name can't be empty".to_string());
+    }
+    Ok(name.to_string())
+}
+
+/// Rename the shortcut(s) pointing at `path`, then persist and rebuild.
+/// Discovered items are regenerated on every load, so they can't be renamed.
+#[tauri::command]
+fn rename_shortcut(
+    app: tauri::AppHandle,
+    state: State<'_, Mutex<AppState>>,
+    path: String,
+    new_name: String,
+) -> Result<(), String> {
+    let name = validate_name(&new_name)?;
+    let mut config = state.lock().map_err(|e| e.to_string())?.config.clone();
+    let mut renamed = 0;
+    for shortcut in config
+        .categories
+        .iter_mut()
+        .filter(|c| !c.discovered)
+        .flat_map(|c| &mut c.shortcuts)
+        .filter(|s| s.path.eq_ignore_ascii_case(&path))
+    {
+        shortcut.name = name.clone();
+        renamed += 1;
+    }
+    if renamed == 0 {
+        return Err(format!("No pinned shortcut for {}", path));
+    }
+    replace_config(&app, &state, config)
+}
+
+/// Rename the category at `index` (as in `config.categories`), then persist
+/// and rebuild.
+#[tauri::command]
+fn rename_category(
+    app: tauri::AppHandle,
+    state: State<'_, Mutex<AppState>>,
+    index: usize,
+    new_name: String,
+) -> Result<(), String> {
+    let name = validate_name(&new_name)?;
+    let mut config = state.lock().map_err(|e| e.to_string())?.config.clone();
+    let category = config
+        .categories
+        .get_mut(index)
+        .ok_or_else(|| format!("No category at index {}", index))?;
+    if category.discovered {
+        return Err(format!(
+            "'{}' is discovered and can't be renamed",
+            category.name
+        ));
+    }
+    category.name = name;
+    replace_config(&app, &state, config)
+}
+
+/// Validate, persist and reload `config` (re-applying discovered items), then
+/// have the dock rebuild.
+fn replace_config(
+    app: &tauri::AppHandle,
+    state: &Mutex<AppState>,
+    config: config::Config,
+) -> Result<(), String> {
+    config::validate(&config).map_err(|e| format!("Invalid config: {}", e))?;
+    // Loading scans folders and resolves shortcuts over COM; keep that (and
+    // the disk write) outside the lock so pollers aren't stalled behind it
+    let config_path = state.lock().map_err(|e| e.to_string())?.config_path.clone();
+    config::save_config(&config_path, &config).map_err(|e| e.to_string())?;
+    let reloaded = config::load_config(&config_path).map_err(|e| e.to_string())?;
+    {
+        let mut state = state.lock().map_err(|e| e.to_string())?;
+        state.config = reloaded;
+        state.config_error = None;
+    }
+    info!("Config saved to {:?}", config_path);
+    apply_topmost(app, state);
+    // Monitor, margin and reflections come from the config too
+    if let Some(window) = app.get_webview_window("main") {
+        update_dock_position(&window, state);
+        apply_backdrop(&window, state);
+    }
+    app.emit_to("main", "config-changed", ())
+        .map_err(|e| e.to_string())
+}
+
+/// How often a config that couldn't be reached at startup is tried again.
+const CONFIG_RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The config was unreachable at startup (e.g. on a network share that isn't
+/// mounted yet): keep trying, and rebuild the dock once it loads. Gives up on
+/// a permanent error such as a parse failure.
+fn load_config_when_available(app: tauri::AppHandle) {
+    std::thread::spawn(move || {
+        let state = app.state::<Mutex<AppState>>();
+        let Ok(config_path) = state.lock().map(|s| s.config_path.clone()) else {
+            return;
+        };
+        loop {
+            std::thread::sleep(CONFIG_RETRY_INTERVAL);
+            let error = match config::load_config_with_retry(&config_path) {
+                Ok(config) => {
+                    if let Ok(mut state) = state.lock() {
+                        state.config = config;
+                        state.config_error = None;
+                    }
+                    info!("Config became available at {:?}", config_path);
+                    apply_topmost(&app, &state);
+                    if let Some(window) = app.get_webview_window("main") {
+                        update_dock_position(&window, &state);
+                        apply_backdrop(&window, &state);
+                    }
+                    let _ = app.emit_to("main", "config-changed", ());
+                    return;
+                }
+                Err(e) => e,
+            };
+            let transient = error
+                .downcast_ref::<std::io::Error>()
+                .is_some_and(config::is_transient);
+            if !transient {
+                warn!("Config at {:?} is unusable: {}", config_path, error);
+                if let Ok(mut state) = state.lock() {
+                    state.config_error = Some(error.to_string());
+                }
+                return;
+            }
+            debug!("Config at {:?} still unavailable: {}", config_path, error);
+        }
+    });
+}
+
+/// Category the foreground app is pinned to when the config has no regular
+/// category yet.
+const PIN_CATEGORY: &str = "Apps";
+
+/// Pin the app owning the foreground window to the first regular category,
+/// then persist and rebuild. Returns the new shortcut's name, or `None` when
+/// the app is already on the dock.
+fn pin_foreground(
+    app: &tauri::AppHandle,
+    state: &Mutex<AppState>,
+) -> Result<Option<String>, String> {
+    let path = process::foreground_app_path().ok_or("No app in the foreground")?;
+    let mut config = state.lock().map_err(|e| e.to_string())?.config.clone();
+
+    let pinned = config
+        .categories
+        .iter()
+        .flat_map(|c| &c.shortcuts)
+        .any(|s| {
+            let pattern = s.process_match.as_deref().unwrap_or(&s.path);
+            !pattern.is_empty() && process::matches_process(pattern, &path)
+        });
+    if pinned {
+        return Ok(None);
+    }
+
+    let name = std::path::Path::new(&path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(&path)
+        .to_string();
+    let shortcut = config::Shortcut {
+        name: name.clone(),
+        path,
+        ..Default::default()
+    };
+    match config.categories.iter_mut().find(|c| !c.discovered) {
+        Some(category) => category.shortcuts.push(shortcut),
+        None => config.categories.push(config::Category {
+            name: PIN_CATEGORY.to_string(),
+            shortcuts: vec![shortcut],
+            source: None,
+            discovered: false,
+            enabled: true,
+            collapsed: false,
+            sort: Default::default(),
+        }),
+    }
+
+    replace_config(app, state, config)?;
+    Ok(Some(name))
+}
+
+#[tauri::command]
+fn pin_foreground_app(
+    app: tauri::AppHandle,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Option<String>, String> {
+    pin_foreground(&app, &state)
+}
+
+/// Apply `config.topmost` to the dock window (the window config starts it
+/// always-on-top).
+fn apply_topmost(app: &tauri::AppHandle, state: &Mutex<AppState>) {
+    let topmost = state
+        .lock()
+        .map(|s| s.config.topmost && !s.safe_mode)
+        .unwrap_or(true);
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.set_always_on_top(topmost);
+    }
+}
+
+/// Toggle a category's `enabled`/`collapsed` flags, persist them, and have
+/// the dock rebuild.
+#[tauri::command]
+fn set_category_flags(
+    app: tauri::AppHandle,
+    state: State<'_, Mutex<AppState>>,
+    name: String,
+    enabled: Option<bool>,
+    collapsed: Option<bool>,
+) -> Result<(), String> {
+    {
+        let mut state = state.lock().map_err(|e| e.to_string())?;
+        let category = state
+            .config
+            .categories
+            .iter_mut()
+            .find(|c| c.name == name)
+            .ok_or_else(|| format!("No category named {}", name))?;
+        if let Some(enabled) = enabled {
+            category.enabled = enabled;
+        }
+        if let Some(collapsed) = collapsed {
+            category.collapsed = collapsed;
+        }
+        config::save_config(&state.config_path, &state.config).map_err(|e| e.to_string())?;
+    }
+    app.emit_to("main", "config-changed", ())
+        .map_err(|e| e.to_string())
+}
+
+/// Which config file is in use and whether it loaded, for "my config isn't
+/// being read" troubleshooting.
+#[derive(Debug, serde::Serialize)]
+struct ConfigInfo {
+    path: String,
+    exists: bool,
+    categories: usize,
+    shortcuts: usize,
+    error: Option<String>,
+}
+
+#[tauri::command]
+fn config_info(state: State<'_, Mutex<AppState>>) -> Result<ConfigInfo, String> {
+    let state = state.lock().map_err(|e| e.to_string())?;
+    let path =
+        std::path::absolute(&state.config_path).unwrap_or_else(|_| state.config_path.clone());
+    Ok(ConfigInfo {
+        path: path.display().to_string(),
+        exists: state.config_path.exists(),
+        categories: state.config.categories.len(),
+        shortcuts: state
+            .config
+            .categories
+            .iter()
+            .map(|c| c.shortcuts.len())
+            .sum(),
+        error: state.config_error.clone(),
+    })
+}
+
+/// JSON Schema of config.json, for editors and scripts.
+#[tauri::command]
+fn config_schema() -> String {
+    config::schema_json()
+}
+
+/// Open the settings window, or focus it if it's already open.
+// Async: creating a window from a sync command can deadlock on Windows.
+#[tauri::command]
+async fn open_settings(app: tauri::AppHandle) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window("settings") {
+        let _ = window.unminimize();
+        return window.set_focus().map_err(|e| e.to_string());
+    }
+
+    tauri::WebviewWindowBuilder::new(
+        &app,
+        "settings",
+        tauri::WebviewUrl::App("settings.html".into()),
+    )
+    .title("OxideDock Settings")
+    .inner_size(640.0, 560.0)
+    .min_inner_size(480.0, 360.0)
+    .build()
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+fn get_accessibility(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<accessibility::AccessibilityModes, String> {
+    let state = state.lock().map_err(|e| e.to_string())?;
+    Ok(accessibility::resolve(&state.config.accessibility))
+}
+
+/// Colour of the selection ring: `style.accent_color`, else the system accent.
+#[tauri::command]
+fn get_accent_color(state: State<'_, Mutex<AppState>>) -> Result<Option<String>, String> {
+    let state = state.lock().map_err(|e| e.to_string())?;
+    Ok(state
+        .style()
+        .accent_color
+        .or_else(accessibility::system_accent_color))
+}
+
+/// SSIDs of the connected Wi-Fi networks, for `visible_when.ssid`.
+#[tauri::command]
+fn get_wifi_ssids() -> Vec<String> {
+    network::connected_ssids()
+}
+
+/// The effective dock style: config values under any runtime override.
+#[tauri::command]
+fn get_style(state: State<'_, Mutex<AppState>>) -> Result<config::DockStyle, String> {
+    let state = state.lock().map_err(|e| e.to_string())?;
+    Ok(state.style())
+}
+
+/// Layer `partial` over the current style until `clear_style_override` or
+/// restart, e.g. for a "focus mode" that dims the dock. Not written to the
+/// config file.
+#[tauri::command]
+fn set_style_override(
+    app: tauri::AppHandle,
+    state: State<'_, Mutex<AppState>>,
+    partial: config::PartialDockStyle,
+) -> Result<(), String> {
+    state
+        .lock()
+        .map_err(|e| e.to_string())?
+        .style_override
+        .merge(partial);
+    apply_style_change(&app, &state)
+}
+
+/// Drop all runtime style overrides, going back to the config's style.
+#[tauri::command]
+fn clear_style_override(
+    app: tauri::AppHandle,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    state.lock().map_err(|e| e.to_string())?.style_override = Default::default();
+    apply_style_change(&app, &state)
+}
+
+#[tauri::command]
+fn get_label_mode(state: State<'_, Mutex<AppState>>) -> Result<config::LabelMode, String> {
+    let state = state.lock().map_err(|e| e.to_string())?;
+    Ok(state.style().label_mode)
+}
+
+/// Show or hide the labels under the icons until restart, without reloading
+/// the dock. Not written to the config file.
+#[tauri::command]
+fn set_label_mode(
+    app: tauri::AppHandle,
+    state: State<'_, Mutex<AppState>>,
+    mode: config::LabelMode,
+) -> Result<(), String> {
+    apply_label_mode(&app, &state, mode)
+}
+
+/// Unlike other style changes this animates in place rather than rebuilding
+/// the dock, so it has its own event.
+fn apply_label_mode(
+    app: &tauri::AppHandle,
+    state: &Mutex<AppState>,
+    mode: config::LabelMode,
+) -> Result<(), String> {
+    state
+        .lock()
+        .map_err(|e| e.to_string())?
+        .style_override
+        .label_mode = Some(mode);
+    if let Some(window) = app.get_webview_window("main") {
+        update_dock_position(&window, state);
+    }
+    app.emit_to("main", "label-mode-changed", mode)
+        .map_err(|e| e.to_string())
+}
+
+/// Margins move the window and the backdrop is set on it; everything else is
+/// restyled by the frontend.
+fn apply_style_change(app: &tauri::AppHandle, state: &Mutex<AppState>) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window("main") {
+        update_dock_position(&window, state);
+        apply_backdrop(&window, state);
+    }
+    app.emit_to("main", "style-changed", ())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_dnd(state: State<'_, Mutex<AppState>>) -> Result<bool, String> {
+    Ok(state.lock().map_err(|e| e.to_string())?.dnd)
+}
+
+/// Turn do not disturb on or off until restart, e.g. while presenting.
+/// Launching keeps working; highlights, bounces and sounds are dropped.
+#[tauri::command]
+fn set_dnd(
+    app: tauri::AppHandle,
+    state: State<'_, Mutex<AppState>>,
+    enabled: bool,
+) -> Result<(), String> {
+    state.lock().map_err(|e| e.to_string())?.dnd = enabled;
+    info!("Do not disturb {}", if enabled { "on" } else { "off" });
+    app.emit_to("main", "dnd-changed", enabled)
+        .map_err(|e| e.to_string())
+}
+
+/// Pulse the dock icon for `path` for `duration_ms` to draw attention to it,
+/// e.g. "your download finished". Paths not on the dock are an error.
+#[tauri::command]
+fn highlight_icon(
+    app: tauri::AppHandle,
+    state: State<'_, Mutex<AppState>>,
+    path: String,
+    duration_ms: u32,
+) -> Result<(), String> {
+    let known = {
+        let state = state.lock().map_err(|e| e.to_string())?;
+        if state.dnd {
+            debug!("Do not disturb: not highlighting {}", path);
+            return Ok(());
+        }
+        state
+            .config
+            .categories
+            .iter()
+            .flat_map(|c| &c.shortcuts)
+            .any(|s| s.path.eq_ignore_ascii_case(&path))
+    };
+    if !known {
+        return Err(format!("No dock icon for {}", path));
+    }
+
+    let duration_ms = duration_ms.min(MAX_HIGHLIGHT.as_millis() as u32);
+    app.emit_to(
+        "main",
+        "highlight-icon",
+        HighlightRequest { path, duration_ms },
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Play the configured `launch` or `hover` sound, if any.
+#[tauri::command]
+fn play_sound(state: State<'_, Mutex<AppState>>, kind: String) -> Result<(), String> {
+    let path = {
+        let state = state.lock().map_err(|e| e.to_string())?;
+        if state.dnd {
+            return Ok(());
+        }
+        match kind.as_str() {
+            "launch" => state.config.launch_sound.clone(),
+            "hover" => state.config.hover_sound.clone(),
+            _ => return Err(format!("Unknown sound {}", kind)),
+        }
+    };
+    if let Some(path) = path {
+        sound::play(&path);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn list_monitors(window: tauri::WebviewWindow) -> Result<Vec<MonitorInfo>, String> {
+    let primary = window
+        .primary_monitor()
+        .map_err(|e| e.to_string())?
+        .and_then(|m| m.name().cloned());
+    let monitors = window.available_monitors().map_err(|e| e.to_string())?;
+    Ok(monitors
+        .into_iter()
+        .map(|m| MonitorInfo {
+            is_primary: m.name().is_some() && m.name() == primary.as_ref(),
+            name: m.name().cloned(),
+            x: m.position().x,
+            y: m.position().y,
+            width: m.size().width,
+            height: m.size().height,
+            scale: m.scale_factor(),
+        })
+        .collect())
+}
+
+#[tauri::command]
+fn get_dock_bounds(
+    window: tauri::WebviewWindow,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<DockBounds, String> {
+    let position = window.outer_position().map_err(|e| e.to_string())?;
+    let size = window.outer_size().map_err(|e| e.to_string())?;
+    let monitor = window
+        .current_monitor()
+        .ok()
+        .flatten()
+        .and_then(|m| m.name().cloned());
+    let state = state.lock().map_err(|e| e.to_string())?;
+    Ok(DockBounds {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+        monitor,
+        edge: "bottom",
+        hidden: state.is_hidden,
+        appbar_height: state.appbar_height,
+    })
+}
+
+#[tauri::command]
+fn is_minimal(state: State<'_, Mutex<AppState>>) -> Result<bool, String> {
+    let state = state.lock().map_err(|e| e.to_string())?;
+    Ok(state.minimal)
+}
+
+#[tauri::command]
+fn is_safe_mode(state: State<'_, Mutex<AppState>>) -> Result<bool, String> {
+    let state = state.lock().map_err(|e| e.to_string())?;
+    Ok(state.safe_mode)
+}
+
+/// Enter or leave minimal mode, giving the taskbar and work area back (or
+/// taking them again), then have the dock rebuild with the lighter settings.
+fn set_minimal(app: &tauri::AppHandle, minimal: bool) {
+    info!("Minimal mode {}", if minimal { "on" } else { "off" });
+    let state = app.state::<Mutex<AppState>>();
+    if let Ok(mut state) = state.lock() {
+        state.minimal = minimal;
+        // Same geometry, different AppBar: force the update through
+        state.dock_geometry = None;
+    }
+
+    if minimal {
+        show_taskbar();
+    } else {
+        hide_taskbar(app);
+    }
+    if let Some(window) = app.get_webview_window("main") {
+        update_dock_position(&window, &state);
+    }
+    let _ = app.emit_to("main", "minimal-changed", minimal);
+}
+
 #[tauri::command]
 fn set_dock_hidden(
     window: tauri::WebviewWindow,
@@ -37,23 +742,142 @@ fn set_dock_hidden(
     Ok(())
 }
 
+/// The frontend laid the bar out anew; clicks outside it go through the dock.
+#[tauri::command]
+fn set_bar_rect(
+    state: State<'_, Mutex<AppState>>,
+    rect: clickthrough::BarRect,
+) -> Result<(), String> {
+    let mut state = state.lock().map_err(|e| e.to_string())?;
+    state.bar_rect = Some(rect);
+    Ok(())
+}
+
+/// `size` is the physical pixel size the frontend will display the icon at;
+/// larger sources are Lanczos3-downscaled to it here rather than by the browser.
+/// `icon_index` picks an embedded icon of a multi-icon executable. With
+/// `animate`, animated GIF/APNG custom icons are passed through unchanged.
+#[tauri::command]
+fn get_icon_base64(
+    path: String,
+    size: Option<u32>,
+    icon_index: Option<i32>,
+    animate: Option<bool>,
+) -> Result<String, String> {
+    icon_data_url(&path, size, icon_index, animate.unwrap_or(false))
+}
+
+/// Re-extract the icon for `path`, e.g. after the target app updated itself.
+#[tauri::command]
+fn refresh_icon(
+    path: String,
+    size: Option<u32>,
+    icon_index: Option<i32>,
+    animate: Option<bool>,
+) -> Result<String, String> {
+    debug!("Refreshing icon: '{}'", path);
+    icon_data_url(&path, size, icon_index, animate.unwrap_or(false))
+}
+
+/// The dock has painted its icons: log the startup timing summary (debug).
+#[tauri::command]
+fn startup_finished() {
+    profile::log_summary();
+}
+
+/// Re-extract every icon on the dock in place, keeping the layout; lighter
+/// than a config reload when only app artwork changed (e.g. after updates).
+#[tauri::command]
+fn reload_icons(app: tauri::AppHandle) -> Result<(), String> {
+    info!("Reloading all icons");
+    app.emit_to("main", "reload-icons", ())
+        .map_err(|e| e.to_string())
+}
+
+/// Write a PNG of the dock as currently drawn to `out`, e.g. for theme
+/// previews and visual regression checks.
+#[tauri::command]
+fn capture_dock(app: tauri::AppHandle, out: String) -> Result<(), String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Dock window not found")?;
+    let hwnd = window.hwnd().map_err(|e| e.to_string())?;
+    let image = capture::capture_window(HWND(hwnd.0 as isize))?;
+    image
+        .save_with_format(&out, image::ImageFormat::Png)
+        .map_err(|e| format!("Failed to write {}: {}", out, e))?;
+    info!("Dock captured to {}", out);
+    Ok(())
+}
+
+/// Which system icon sizes Windows has for `path`, for diagnosing blurry icons.
 #[tauri::command]
-fn get_icon_base64(path: String) -> Result<Option<String>, String> {
-    if let Some(img) = icon_extractor::extract_icon(&path) {
-        let (w, h) = (img.width(), img.height());
-        let mut png_bytes: Vec<u8> = Vec::new();
-        let encoder = image::codecs::png::PngEncoder::new(&mut png_bytes);
-        if encoder
-            .write_image(&img, w, h, image::ExtendedColorType::Rgba8)
-            .is_ok()
-        {
-            return Ok(Some(format!(
-                "data:image/png;base64,{}",
-                BASE64.encode(&png_bytes)
-            )));
+fn icon_tiers(path: String) -> Result<Vec<icon_extractor::IconTierInfo>, String> {
+    if !std::path::Path::new(&path).exists() {
+        return Err(format!("{} does not exist", path));
+    }
+    Ok(icon_extractor::icon_tiers(&path))
+}
+
+fn icon_data_url(
+    path: &str,
+    size: Option<u32>,
+    icon_index: Option<i32>,
+    animate: bool,
+) -> Result<String, String> {
+    // Vector icons stay vector: the webview rasterizes them at draw size
+    if icon_index.is_none() {
+        if let Some(svg) = icon_extractor::svg_image(path) {
+            return Ok(format!("data:image/svg+xml;base64,{}", BASE64.encode(&svg)));
         }
     }
-    Ok(None)
+
+    // The webview plays (and scales) animations itself
+    if animate && icon_index.is_none() {
+        if let Some((mime, bytes)) = icon_extractor::animated_image(path) {
+            return Ok(format!("data:{};base64,{}", mime, BASE64.encode(&bytes)));
+        }
+    }
+
+    let started = Instant::now();
+    let extracted = icon_extractor::extract_icon(path, icon_index);
+    profile::record_icon(path, started.elapsed());
+    let mut img = extracted.map_err(|e| format!("No icon for {}: {}", path, e))?;
+    if let Some(size) = size.filter(|&s| s > 0 && s < img.width().max(img.height())) {
+        img = image::imageops::resize(&img, size, size, image::imageops::FilterType::Lanczos3);
+    }
+
+    let (w, h) = (img.width(), img.height());
+    let mut png_bytes: Vec<u8> = Vec::new();
+    let encoder = image::codecs::png::PngEncoder::new(&mut png_bytes);
+    encoder
+        .write_image(&img, w, h, image::ExtendedColorType::Rgba8)
+        .map_err(|e| e.to_string())?;
+    Ok(format!(
+        "data:image/png;base64,{}",
+        BASE64.encode(&png_bytes)
+    ))
+}
+
+/// Copy what a shortcut points at (the `.lnk` target, else the path itself).
+/// Returns the copied path.
+#[tauri::command]
+fn copy_shortcut_path(window: tauri::WebviewWindow, path: String) -> Result<String, String> {
+    let target = config::shortcut_target(&path);
+    let hwnd = window.hwnd().map_err(|e| e.to_string())?;
+    transfer::copy_text(HWND(hwnd.0 as isize), &target).map_err(|e| e.to_string())?;
+    info!("Copied path of '{}': {}", path, target);
+    Ok(target)
+}
+
+/// Start dragging a shortcut's target out of the dock as a file. Must run on
+/// the UI thread (sync command) with the mouse button still down; returns
+/// once it's dropped or cancelled.
+#[tauri::command]
+fn drag_shortcut_out(window: tauri::WebviewWindow, path: String) -> Result<(), String> {
+    let target = config::shortcut_target(&path);
+    let hwnd = window.hwnd().map_err(|e| e.to_string())?;
+    transfer::drag_file(HWND(hwnd.0 as isize), &target).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -64,114 +888,673 @@ fn launch_app(path: String) -> Result<(), String> {
     Ok(())
 }
 
+/// Apply a shortcut's `env` and `env_clear` to the process about to start.
+fn apply_env(command: &mut std::process::Command, shortcut: &config::Shortcut) {
+    if shortcut.env_clear {
+        command.env_clear();
+    }
+    if let Some(env) = &shortcut.env {
+        command.envs(
+            env.iter()
+                .map(|(name, value)| (name, config::expand_env_vars(value))),
+        );
+    }
+}
+
+/// Ask `message` in a Yes/No box titled `name` before a `confirm` shortcut
+/// runs. Blocks a worker thread, not the UI, until answered.
 #[tauri::command]
-fn get_running_apps() -> Result<Vec<String>, String> {
-    #[cfg(target_os = "windows")]
-    {
-        use windows::Win32::Foundation::CloseHandle;
-        use windows::Win32::System::ProcessStatus::EnumProcesses;
-        use windows::Win32::System::Threading::{
-            OpenProcess, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
-            QueryFullProcessImageNameW,
+async fn confirm_launch(name: String, message: String) -> Result<bool, String> {
+    tauri::async_runtime::spawn_blocking(move || unsafe {
+        MessageBoxW(
+            HWND::default(),
+            &windows::core::HSTRING::from(message),
+            &windows::core::HSTRING::from(name),
+            MB_YESNO | MB_ICONQUESTION | MB_TOPMOST,
+        ) == IDYES
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Launch `path` elevated via the "runas" verb, which shows the UAC prompt.
+fn launch_elevated(path: &str) -> Result<(), String> {
+    let wide: Vec<u16> = path.encode_utf16().chain(std::iter::once(0)).collect();
+    let result = unsafe {
+        ShellExecuteW(
+            HWND::default(),
+            w!("runas"),
+            PCWSTR(wide.as_ptr()),
+            PCWSTR::null(),
+            PCWSTR::null(),
+            SW_SHOWNORMAL,
+        )
+    };
+    // 32 and below are error codes; declining the UAC prompt is one of them
+    if result.0 <= 32 {
+        return Err(format!(
+            "Failed to launch {} as administrator (code {})",
+            path, result.0
+        ));
+    }
+    Ok(())
+}
+
+/// Open `files` with the app at `path` (files dropped on its dock icon).
+#[tauri::command]
+fn open_with(path: String, files: Vec<String>) -> Result<(), String> {
+    std::process::Command::new(&path)
+        .args(&files)
+        .spawn()
+        .map_err(|e| {
+            format!(
+                "Failed to open {} file(s) with {}: {}",
+                files.len(),
+                path,
+                e
+            )
+        })?;
+    Ok(())
+}
+
+/// Start each of `apps` in turn, waiting `delays_ms[i]` after the i-th one.
+/// Every app is attempted; failures are reported together.
+fn launch_group(apps: &[String], delays_ms: &[u64]) -> Result<(), String> {
+    let mut errors = Vec::new();
+    for (i, path) in apps.iter().enumerate() {
+        if let Err(e) = launch_app(path.clone()) {
+            warn!("{}", e);
+            errors.push(e);
+            continue;
+        }
+        let delay = delays_ms.get(i).copied().unwrap_or(0);
+        if delay > 0 && i + 1 < apps.len() {
+            std::thread::sleep(Duration::from_millis(delay));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("; "))
+    }
+}
+
+/// A shortcut's action failed, for the dock to flag the icon (and say why).
+#[derive(Debug, Clone, serde::Serialize)]
+struct LaunchFailure {
+    path: String,
+    error: String,
+}
+
+/// Run a shortcut's click action. Builtins are dock UI actions and are
+/// dispatched by the frontend before it gets here. Failures are also emitted
+/// as "launch-failed", so every caller gets the same visible feedback.
+// Async: launch groups sleep between targets, which must not stall the UI.
+#[tauri::command]
+async fn run_action(
+    app: tauri::AppHandle,
+    state: State<'_, Mutex<AppState>>,
+    shortcut: config::Shortcut,
+) -> Result<(), String> {
+    let path = shortcut.path.clone();
+    let result = perform_action(&app, &state, shortcut).await;
+    if let Err(error) = &result {
+        warn!("Action on '{}' failed: {}", path, error);
+        let failure = LaunchFailure {
+            path,
+            error: error.clone(),
         };
+        let _ = app.emit_to("main", "launch-failed", failure);
+    }
+    result
+}
 
-        let mut pids = [0u32; 1024];
-        let mut cb_needed = 0u32;
-        unsafe {
-            if EnumProcesses(pids.as_mut_ptr(), (pids.len() as u32) * 4, &mut cb_needed).is_ok() {
-                let count = (cb_needed / 4) as usize;
-                let mut paths = std::collections::HashSet::new();
-
-                for i in 0..count {
-                    let pid = pids[i];
-                    if pid == 0 {
-                        continue;
-                    }
+async fn perform_action(
+    app: &tauri::AppHandle,
+    state: &Mutex<AppState>,
+    shortcut: config::Shortcut,
+) -> Result<(), String> {
+    let counted = !matches!(
+        shortcut.action,
+        config::ShortcutAction::Builtin { .. } | config::ShortcutAction::Unknown
+    );
+    if counted {
+        // Written after the guard drops, so a slow profile folder doesn't
+        // stall every other command behind the lock
+        let snapshot = state.lock().map(|mut s| s.usage.record(&shortcut)).ok();
+        if let Some((path, counts)) = snapshot {
+            usage::save(&path, &counts);
+        }
+    }
 
-                    if let Ok(handle) = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) {
-                        let mut buffer = [0u16; 1024];
-                        let mut size = buffer.len() as u32;
-                        if QueryFullProcessImageNameW(
-                            handle,
-                            PROCESS_NAME_WIN32,
-                            windows::core::PWSTR(buffer.as_mut_ptr()),
-                            &mut size,
-                        )
-                        .is_ok()
-                        {
-                            let path = String::from_utf16_lossy(&buffer[..size as usize]);
-                            paths.insert(path.to_lowercase());
-                        }
-                        let _ = CloseHandle(handle);
-                    }
-                }
-                return Ok(paths.into_iter().collect());
+    match shortcut.action {
+        config::ShortcutAction::Launch => {
+            let pattern = shortcut.process_match.as_deref().unwrap_or(&shortcut.path);
+            if click_focused(state, pattern) {
+                return Ok(());
+            }
+            if shortcut.single_instance && process::focus_app(pattern) {
+                return Ok(());
+            }
+            if shortcut.run_as_admin {
+                return launch_elevated(&shortcut.path);
             }
+            if shortcut.env.is_none() && !shortcut.env_clear {
+                return launch_app(shortcut.path);
+            }
+            let mut command = std::process::Command::new(&shortcut.path);
+            apply_env(&mut command, &shortcut);
+            command
+                .spawn()
+                .map_err(|e| format!("Failed to launch {}: {}", shortcut.path, e))?;
+            Ok(())
         }
+        config::ShortcutAction::Command { ref cmd, ref args } => {
+            let mut command = std::process::Command::new(cmd);
+            command.args(args);
+            apply_env(&mut command, &shortcut);
+            command
+                .spawn()
+                .map_err(|e| format!("Failed to run {}: {}", cmd, e))?;
+            Ok(())
+        }
+        config::ShortcutAction::LaunchGroup { apps, delays_ms } => {
+            tauri::async_runtime::spawn_blocking(move || launch_group(&apps, &delays_ms))
+                .await
+                .map_err(|e| e.to_string())?
+        }
+        config::ShortcutAction::OpenFolder => app
+            .opener()
+            .open_path(&shortcut.path, None::<&str>)
+            .map_err(|e| format!("Failed to open {}: {}", shortcut.path, e)),
+        config::ShortcutAction::Url => app
+            .opener()
+            .open_url(&shortcut.path, None::<&str>)
+            .map_err(|e| format!("Failed to open {}: {}", shortcut.path, e)),
+        config::ShortcutAction::Builtin { name } => {
+            warn!("Builtin action '{}' reached the backend, ignoring", name);
+            Ok(())
+        }
+        config::ShortcutAction::Unknown => {
+            warn!("Unknown action on '{}', ignoring", shortcut.name);
+            Ok(())
+        }
+    }
+}
+
+/// Launch counts keyed as in `usage::key`, for frequency sorting.
+#[tauri::command]
+fn get_launch_counts(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<std::collections::HashMap<String, u64>, String> {
+    let state = state.lock().map_err(|e| e.to_string())?;
+    Ok(state.usage.counts().clone())
+}
+
+/// Keys (as in `usage::key`) of shortcuts added or discovered since and not
+/// clicked yet, for the "new" badge.
+#[tauri::command]
+fn get_new_items(state: State<'_, Mutex<AppState>>) -> Result<Vec<String>, String> {
+    let state = state.lock().map_err(|e| e.to_string())?;
+    Ok(state.seen.new_keys(&state.config))
+}
+
+/// A new shortcut was clicked: drop its badge for good.
+#[tauri::command]
+fn mark_seen(state: State<'_, Mutex<AppState>>, key: String) -> Result<(), String> {
+    // Written after the guard drops, like the launch counts
+    let snapshot = state.lock().map_err(|e| e.to_string())?.seen.mark(key);
+    if let Some((path, seen)) = snapshot {
+        seen::save(&path, &seen);
+    }
+    Ok(())
+}
+
+/// Default for `running_cache_ms`.
+const RUNNING_CACHE_TTL: Duration = Duration::from_secs(1);
+
+/// Image paths of running processes. A scan younger than `running_cache_ms`
+/// is reused unless `force` is set, since each scan opens every process.
+#[tauri::command]
+fn get_running_apps(
+    state: State<'_, Mutex<AppState>>,
+    force: Option<bool>,
+) -> Result<Vec<String>, String> {
+    running_paths(&state, force.unwrap_or(false))
+}
+
+/// Whether the app at `path` is running, from the same cached scan as
+/// `get_running_apps`. A pinned shortcut's `process_match` is used in place
+/// of its path.
+#[tauri::command]
+fn is_app_running(state: State<'_, Mutex<AppState>>, path: String) -> Result<bool, String> {
+    let pattern = process_pattern(&state.lock().map_err(|e| e.to_string())?.config, path);
+    let running = running_paths(&state, false)?;
+    Ok(is_running(&pattern, &running))
+}
+
+/// What to look for in the process list for `path`: the `process_match` of
+/// the shortcut with that path (compared case-insensitively), else the path.
+fn process_pattern(config: &config::Config, path: String) -> String {
+    config
+        .categories
+        .iter()
+        .flat_map(|c| &c.shortcuts)
+        .find(|s| s.path.eq_ignore_ascii_case(&path))
+        .and_then(|s| s.process_match.clone())
+        .unwrap_or(path)
+}
+
+fn is_running(pattern: &str, running: &[String]) -> bool {
+    !pattern.is_empty()
+        && running
+            .iter()
+            .any(|path| process::matches_process(pattern, path))
+}
+
+/// Running process paths minus `running_ignore`, scanning only when the
+/// cached scan is stale or `force` is set.
+fn running_paths(state: &Mutex<AppState>, force: bool) -> Result<Vec<String>, String> {
+    let (ignore, cached) = {
+        let state = state.lock().map_err(|e| e.to_string())?;
+        let ttl = state
+            .config
+            .running_cache_ms
+            .map(Duration::from_millis)
+            .unwrap_or(RUNNING_CACHE_TTL);
+        let cached = state
+            .running_cache
+            .as_ref()
+            .filter(|(at, _)| !force && at.elapsed() < ttl)
+            .map(|(_, paths)| paths.clone());
+        (state.config.running_ignore.clone(), cached)
+    };
+
+    let paths = match cached {
+        Some(paths) => paths,
+        None => {
+            // Scanned outside the lock
+            let paths = process::running_app_paths();
+            let mut state = state.lock().map_err(|e| e.to_string())?;
+            state.running_cache = Some((Instant::now(), paths.clone()));
+            paths
+        }
+    };
+    Ok(without_ignored(paths, &ignore))
+}
+
+/// Apps that currently own a visible top-level window.
+#[tauri::command]
+fn get_windowed_apps(state: State<'_, Mutex<AppState>>) -> Result<Vec<String>, String> {
+    let ignore = running_ignore_list(&state)?;
+    Ok(without_ignored(process::windowed_app_paths(), &ignore))
+}
+
+/// Open windows per app, for the running indicator's dots.
+#[tauri::command]
+fn get_window_counts(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<std::collections::HashMap<String, usize>, String> {
+    let ignore = running_ignore_list(&state)?;
+    Ok(process::window_counts()
+        .into_iter()
+        .filter(|(path, _)| !ignore.iter().any(|p| process::matches_process(p, path)))
+        .collect())
+}
+
+/// Scroll-wheel cycling through an app's windows.
+#[tauri::command]
+fn cycle_app_windows(path: String, forward: bool) -> Result<(), String> {
+    if process::cycle_app_windows(&path, forward) {
+        Ok(())
+    } else {
+        Err(format!("No window found for {}", path))
     }
-    Ok(vec![])
 }
 
-fn hide_taskbar() {
+fn running_ignore_list(state: &Mutex<AppState>) -> Result<Vec<String>, String> {
+    let state = state.lock().map_err(|e| e.to_string())?;
+    Ok(state.config.running_ignore.clone())
+}
+
+fn without_ignored(paths: Vec<String>, ignore: &[String]) -> Vec<String> {
+    paths
+        .into_iter()
+        .filter(|path| !ignore.iter().any(|p| process::matches_process(p, path)))
+        .collect()
+}
+
+/// Grace period `close_app` gives an app to exit after WM_CLOSE by default.
+const CLOSE_GRACE: Duration = Duration::from_secs(5);
+
+/// Close an app's windows, waiting `grace_ms` (default 5s) for it to exit and
+/// terminating it afterwards only with `force`.
+// Async: waiting out the grace period must not stall the UI.
+#[tauri::command]
+async fn close_app(
+    path: String,
+    grace_ms: Option<u64>,
+    force: Option<bool>,
+) -> Result<process::CloseOutcome, String> {
+    let grace = grace_ms.map(Duration::from_millis).unwrap_or(CLOSE_GRACE);
+    let force = force.unwrap_or(false);
+    let outcome = tauri::async_runtime::spawn_blocking({
+        let path = path.clone();
+        move || process::close_app(&path, grace, force)
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    outcome.ok_or_else(|| format!("No window found for {}", path))
+}
+
+/// Apply `click_focused_action` when `pattern`'s app was the focused one.
+/// Returns whether it replaced the normal focus/launch.
+fn click_focused(state: &Mutex<AppState>, pattern: &str) -> bool {
+    let action = state
+        .lock()
+        .map(|s| s.config.click_focused_action)
+        .unwrap_or_default();
+    let others = match action {
+        config::ClickFocusedAction::Nothing => return false,
+        config::ClickFocusedAction::Minimize => false,
+        config::ClickFocusedAction::MinimizeOthers => true,
+    };
+    if !process::was_foreground(pattern) {
+        return false;
+    }
+    process::minimize_windows(pattern, others);
+    if others {
+        // Clicking the dock took focus from it
+        process::focus_app(pattern);
+    }
+    true
+}
+
+#[tauri::command]
+fn focus_app(state: State<'_, Mutex<AppState>>, path: String) -> Result<(), String> {
+    if click_focused(&state, &path) {
+        return Ok(());
+    }
+    if process::focus_app(&path) {
+        Ok(())
+    } else {
+        Err(format!("No window found for {}", path))
+    }
+}
+
+/// Taskbars hidden by `hide_taskbar`, restored by `show_taskbar`. Outside
+/// `AppState` so a failed startup can still restore them.
+static HIDDEN_TASKBARS: Mutex<Vec<isize>> = Mutex::new(Vec::new());
+
+/// The primary taskbar and one per secondary monitor.
+fn taskbar_windows() -> Vec<HWND> {
+    let mut taskbars = Vec::new();
     unsafe {
-        let taskbar_hwnd = FindWindowW(w!("Shell_TrayWnd"), None);
-        if taskbar_hwnd != HWND::default() {
-            ShowWindow(taskbar_hwnd, SW_HIDE);
+        let primary = FindWindowW(w!("Shell_TrayWnd"), None);
+        if primary != HWND::default() {
+            taskbars.push(primary);
+        }
+        let mut after = HWND::default();
+        loop {
+            after = FindWindowExW(
+                HWND::default(),
+                after,
+                w!("Shell_SecondaryTrayWnd"),
+                PCWSTR::null(),
+            );
+            if after == HWND::default() {
+                break;
+            }
+            taskbars.push(after);
         }
     }
+    taskbars
 }
 
-fn show_taskbar() {
+/// Hide the taskbars per `config.hide_taskbars`: all of them, or only the
+/// one on the dock's monitor. Any hidden earlier that no longer qualify
+/// (the dock moved monitors) come back.
+fn hide_taskbar(app: &tauri::AppHandle) {
+    let scope = app
+        .state::<Mutex<AppState>>()
+        .lock()
+        .map(|s| s.config.hide_taskbars)
+        .unwrap_or_default();
+    let dock_monitor = app
+        .get_webview_window("main")
+        .and_then(|w| w.hwnd().ok())
+        .map(|hwnd| unsafe { MonitorFromWindow(HWND(hwnd.0 as isize), MONITOR_DEFAULTTONEAREST) });
+
+    let targets: Vec<HWND> = taskbar_windows()
+        .into_iter()
+        .filter(|&taskbar| match (scope, dock_monitor) {
+            (config::TaskbarScope::DockMonitor, Some(monitor)) => {
+                (unsafe { MonitorFromWindow(taskbar, MONITOR_DEFAULTTONEAREST) }) == monitor
+            }
+            _ => true,
+        })
+        .collect();
+
+    let Ok(mut hidden) = HIDDEN_TASKBARS.lock() else {
+        return;
+    };
     unsafe {
-        let taskbar_hwnd = FindWindowW(w!("Shell_TrayWnd"), None);
-        if taskbar_hwnd != HWND::default() {
-            ShowWindow(taskbar_hwnd, SW_SHOW);
+        for &taskbar in hidden.iter() {
+            if !targets.iter().any(|t| t.0 == taskbar) {
+                ShowWindow(HWND(taskbar), SW_SHOW);
+            }
+        }
+        for &taskbar in &targets {
+            ShowWindow(taskbar, SW_HIDE);
+        }
+    }
+    *hidden = targets.into_iter().map(|t| t.0).collect();
+}
+
+/// Bring back every taskbar `hide_taskbar` hid; with none recorded (e.g.
+/// startup failed early), all of them.
+fn show_taskbar() {
+    let recorded = HIDDEN_TASKBARS
+        .lock()
+        .map(|mut hidden| std::mem::take(&mut *hidden))
+        .unwrap_or_default();
+    let taskbars: Vec<HWND> = if recorded.is_empty() {
+        taskbar_windows()
+    } else {
+        recorded.into_iter().map(HWND).collect()
+    };
+    for taskbar in taskbars {
+        unsafe {
+            ShowWindow(taskbar, SW_SHOW);
+        }
+    }
+}
+
+/// Exit cleanly: destroying the dock runs its AppBar/taskbar cleanup first.
+fn quit(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.destroy();
+    }
+    app.exit(0);
+}
+
+/// Quit from the frontend, e.g. the kiosk idle timeout.
+#[tauri::command]
+fn quit_dock(app: tauri::AppHandle) {
+    info!("Quit requested by the dock");
+    quit(&app);
+}
+
+/// Hide the taskbar again and reposition the dock after the shell changed
+/// under us. `force` redoes the AppBar reservation even if the geometry is
+/// unchanged, e.g. after Explorer restarted and forgot it.
+fn reapply_shell_state(app: &tauri::AppHandle, force: bool) {
+    let state = app.state::<Mutex<AppState>>();
+    let minimal = match state.lock() {
+        Ok(mut state) => {
+            if force {
+                state.dock_geometry = None;
+            }
+            state.minimal
+        }
+        Err(_) => return,
+    };
+
+    if !minimal {
+        hide_taskbar(app);
+    }
+    if let Some(window) = app.get_webview_window("main") {
+        update_dock_position(&window, &state);
+    }
+}
+
+/// Turn the system blur behind the window on or off (`style.backdrop`). Where
+/// it isn't available the bar stays flat.
+fn apply_backdrop(window: &tauri::WebviewWindow, state: &Mutex<AppState>) {
+    let Ok(backdrop) = state.lock().map(|s| s.style().backdrop) else {
+        return;
+    };
+
+    #[cfg(target_os = "windows")]
+    {
+        let result = match backdrop {
+            config::Backdrop::Blur => window_vibrancy::apply_blur(window, None),
+            config::Backdrop::Flat => window_vibrancy::clear_blur(window),
+        };
+        if let Err(e) = result {
+            warn!(
+                "Backdrop {:?} unavailable, keeping the flat bar: {}",
+                backdrop, e
+            );
         }
     }
 }
 
 // ─── Positioning and AppBar logic ───
 
+/// Extra window height for the names under the icons (`LabelMode::IconsAndLabels`)
+const LABEL_ROW_HEIGHT: i32 = 18;
+
+/// Extra window height below the bar for icon reflections
+const REFLECTION_HEIGHT: i32 = 14;
+
+/// The monitor the dock belongs on: the configured one if it's connected
+/// (else the primary), or whichever it's on now when none is configured.
+fn dock_monitor(window: &tauri::WebviewWindow, name: Option<&str>) -> Option<tauri::Monitor> {
+    let Some(name) = name else {
+        return window.current_monitor().ok().flatten();
+    };
+    let configured = window.available_monitors().ok().and_then(|monitors| {
+        monitors
+            .into_iter()
+            .find(|m| m.name().is_some_and(|n| n.eq_ignore_ascii_case(name)))
+    });
+    configured.or_else(|| {
+        warn!(
+            "Monitor {:?} isn't connected, using the primary monitor",
+            name
+        );
+        window.primary_monitor().ok().flatten()
+    })
+}
+
 fn update_dock_position(window: &tauri::WebviewWindow, state_mutex: &Mutex<AppState>) {
-    let is_hidden = state_mutex.lock().map(|s| s.is_hidden).unwrap_or(false);
+    let (is_hidden, minimal, margin_x, margin_y, labels, reflections, monitor_name) = state_mutex
+        .lock()
+        .map(|s| {
+            let style = s.style();
+            let labels = style.label_mode == config::LabelMode::IconsAndLabels;
+            (
+                s.is_hidden,
+                s.minimal,
+                style.margin_x,
+                style.margin_y,
+                labels,
+                style.reflections,
+                s.config.monitor.clone(),
+            )
+        })
+        .unwrap_or((false, false, 0, 0, false, false, None));
 
-    if let Some(monitor) = window.current_monitor().ok().flatten() {
+    if let Some(monitor) = dock_monitor(window, monitor_name.as_deref()) {
         let screen_size = monitor.size();
         let scale = monitor.scale_factor();
         let monitor_pos = monitor.position();
 
-        let logical_dock_height = 82; // Optimized Height
+        let mut logical_dock_height = 82; // Optimized Height
+        if labels {
+            logical_dock_height += LABEL_ROW_HEIGHT;
+        }
+        if reflections {
+            logical_dock_height += REFLECTION_HEIGHT;
+        }
         let phys_dock_h = (logical_dock_height as f64 * scale).round() as i32;
 
+        // Margins float the bar away from its edge; clamp so it stays on-screen
+        let screen_w = screen_size.width as i32;
+        let screen_h = screen_size.height as i32;
+        let phys_margin_x = ((margin_x.max(0) as f64 * scale).round() as i32).min(screen_w / 4);
+        let phys_margin_y =
+            ((margin_y.max(0) as f64 * scale).round() as i32).min(screen_h - phys_dock_h);
+
         let phys_bottom_y = if is_hidden {
-            // Hidden: Only 4 pixels visible
-            monitor_pos.y + screen_size.height as i32 - 4
+            // Hidden: Only 4 pixels visible, flush with the edge so it can be revealed
+            monitor_pos.y + screen_h - 4
         } else {
-            monitor_pos.y + screen_size.height as i32 - phys_dock_h
+            monitor_pos.y + screen_h - phys_dock_h - phys_margin_y
         };
 
-        let phys_left_x = monitor_pos.x;
+        let phys_left_x = monitor_pos.x + phys_margin_x;
 
-        // Apply window size and position (Physical)
-        let _ = window.set_size(tauri::PhysicalSize::new(
-            screen_size.width,
-            phys_dock_h as u32,
-        ));
-        let _ = window.set_position(tauri::PhysicalPosition::new(phys_left_x, phys_bottom_y));
+        let geometry = DockGeometry {
+            x: phys_left_x,
+            y: phys_bottom_y,
+            width: (screen_w - 2 * phys_margin_x) as u32,
+            height: phys_dock_h as u32,
+        };
+        // Checked against the window itself, not the last geometry: Explorer,
+        // a DPI change or another app may have moved it. Only differences are
+        // applied, so the Moved/Resized handler doesn't feed itself.
+        let size = tauri::PhysicalSize::new(geometry.width, geometry.height);
+        let position = tauri::PhysicalPosition::new(geometry.x, geometry.y);
+        if window.outer_size().ok() != Some(size) {
+            let _ = window.set_size(size);
+        }
+        if window.outer_position().ok() != Some(position) {
+            let _ = window.set_position(position);
+        }
 
+        let changed = match state_mutex.lock() {
+            Ok(mut state) => state.dock_geometry.replace(geometry) != Some(geometry),
+            Err(_) => true,
+        };
+        #[cfg(not(target_os = "windows"))]
+        let _ = changed;
+        // Nothing moved: don't thrash the AppBar registration
         #[cfg(target_os = "windows")]
-        {
+        if changed {
             if let Ok(hwnd_raw) = window.hwnd() {
                 let hwnd = HWND(hwnd_raw.0 as isize);
                 unregister_appbar(hwnd); // Clear previous area
 
-                if !is_hidden {
+                let reserved = if !is_hidden && !minimal {
+                    // Reserve the margin too, so maximized windows stop above the bar
                     register_appbar(
                         hwnd,
-                        phys_dock_h,
-                        screen_size.width as i32,
-                        screen_size.height as i32,
-                    );
+                        phys_dock_h + phys_margin_y,
+                        RECT {
+                            left: monitor_pos.x,
+                            top: monitor_pos.y,
+                            right: monitor_pos.x + screen_w,
+                            bottom: monitor_pos.y + screen_h,
+                        },
+                    )
+                } else {
+                    None
+                };
+                if let Ok(mut state) = state_mutex.lock() {
+                    state.appbar_height = reserved;
                 }
             }
         }
@@ -180,7 +1563,9 @@ fn update_dock_position(window: &tauri::WebviewWindow, state_mutex: &Mutex<AppSt
 
 // ── AppBar: reserve screen space so maximized windows don't cover the dock ──
 
-fn register_appbar(hwnd: HWND, dock_height: i32, screen_width: i32, screen_height: i32) {
+/// Reserves the bottom of `monitor` (physical screen coordinates). Returns
+/// the height actually reserved, which the shell may have adjusted.
+fn register_appbar(hwnd: HWND, dock_height: i32, monitor: RECT) -> Option<i32> {
     unsafe {
         let mut abd = APPBARDATA {
             cbSize: std::mem::size_of::<APPBARDATA>() as u32,
@@ -191,25 +1576,24 @@ fn register_appbar(hwnd: HWND, dock_height: i32, screen_width: i32, screen_heigh
         // Register as an AppBar
         let result = SHAppBarMessage(ABM_NEW, &mut abd);
         if result == 0 {
-            eprintln!("AppBar: ABM_NEW failed");
-            return;
+            error!("AppBar: ABM_NEW failed");
+            return None;
         }
-        println!("AppBar: Registered successfully");
+        debug!("AppBar: Registered successfully");
 
         // Set position at screen bottom
         abd.uEdge = ABE_BOTTOM as u32;
         abd.rc = RECT {
-            left: 0,
-            top: screen_height - dock_height,
-            right: screen_width,
-            bottom: screen_height,
+            top: monitor.bottom - dock_height,
+            ..monitor
         };
 
         SHAppBarMessage(ABM_SETPOS, &mut abd);
-        println!(
+        info!(
             "AppBar: Reserved bottom {}px (top={}, bottom={})",
             dock_height, abd.rc.top, abd.rc.bottom
         );
+        Some(abd.rc.bottom - abd.rc.top)
     }
 }
 
@@ -221,10 +1605,13 @@ fn unregister_appbar(hwnd: HWND) {
             ..Default::default()
         };
         SHAppBarMessage(ABM_REMOVE, &mut abd);
-        println!("AppBar: Unregistered");
+        debug!("AppBar: Unregistered");
     }
 }
 
+/// The first existing config among the exe's folder, the working directory
+/// and `src-tauri/` (dev runs). With none, the exe's folder: at autostart the
+/// working directory is System32, where a starter config must not land.
 fn find_config() -> std::path::PathBuf {
     let exe_dir = std::env::current_exe()
         .ok()
@@ -238,54 +1625,228 @@ fn find_config() -> std::path::PathBuf {
 
     for candidate in candidates.iter().flatten() {
         if candidate.exists() {
-            println!("Found config at: {:?}", candidate);
             return candidate.clone();
         }
     }
 
-    std::path::PathBuf::from("config.json")
+    exe_dir
+        .map(|d| d.join("config.json"))
+        .unwrap_or_else(|| std::path::PathBuf::from("config.json"))
+}
+
+/// Safe mode, for telling rendering bugs from shell-integration ones: the
+/// `--safe` flag, or `OXIDEDOCK_SAFE` set to anything but empty or `0`.
+fn safe_mode_requested() -> bool {
+    std::env::args().skip(1).any(|arg| arg == "--safe")
+        || std::env::var("OXIDEDOCK_SAFE").is_ok_and(|v| !v.is_empty() && v != "0")
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    profile::start();
+    // Up before the config loads, so its warnings aren't lost
+    logging::init();
     let config_path = find_config();
-    let config = config::load_config(&config_path).unwrap_or_else(|e| {
-        eprintln!("Failed to load config from {:?}: {}", config_path, e);
-        config::Config { categories: vec![] }
-    });
+    let starter = config::write_starter_config(&config_path);
+    let loaded = config::load_config_with_retry(&config_path);
+
+    // The log file lives in the config, so it's attached once that's loaded
+    let log_file = loaded.as_ref().ok().and_then(|c| c.log_file.clone());
+    logging::attach_file(log_file.as_deref().map(std::path::Path::new));
+
+    match starter {
+        Some(Ok(count)) => info!(
+            "No config found, wrote a starter config with {} shortcuts to {:?}",
+            count, config_path
+        ),
+        Some(Err(e)) => warn!(
+            "Failed to write a starter config to {:?}: {}",
+            config_path, e
+        ),
+        None => {}
+    }
+
+    // Still unreachable after the retries: keep trying in the background
+    let unavailable = loaded
+        .as_ref()
+        .err()
+        .and_then(|e| e.downcast_ref::<std::io::Error>())
+        .is_some_and(config::is_transient);
+
+    let (config, config_error) = match loaded {
+        Ok(config) => (config, None),
+        Err(e) => {
+            warn!("Failed to load config from {:?}: {}", config_path, e);
+            (config::Config::default(), Some(e.to_string()))
+        }
+    };
+
+    info!(
+        "Config loaded from {:?}: {} categories",
+        config_path,
+        config.categories.len()
+    );
 
-    println!("Config loaded: {} categories", config.categories.len());
+    config::write_schema(&config_path);
 
-    tauri::Builder::default()
+    sound::warn_missing(&[
+        config.launch_sound.as_deref(),
+        config.hover_sound.as_deref(),
+    ]);
+
+    let safe_mode = safe_mode_requested();
+    if safe_mode {
+        info!("Safe mode: leaving the taskbar, AppBar, topmost and shell hooks alone");
+    }
+    // Safe mode implies minimal: the taskbar stays and nothing is reserved
+    let minimal = safe_mode || power::minimal_active(config.minimal_mode);
+    let dnd = config.do_not_disturb;
+    let usage = usage::UsageStore::load(config_path.with_file_name("usage.json"));
+    let seen = seen::SeenStore::load(config_path.with_file_name("seen.json"), &config);
+
+    let result = tauri::Builder::default()
         .manage(Mutex::new(AppState {
             config,
+            config_path,
+            config_error,
             is_hidden: false,
+            dock_geometry: None,
+            minimal,
+            appbar_height: None,
+            safe_mode,
+            usage,
+            seen,
+            running_cache: None,
+            style_override: Default::default(),
+            dnd,
+            bar_rect: None,
         }))
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .invoke_handler(tauri::generate_handler![
             get_config,
+            get_dock_model,
+            save_config,
+            export_config,
+            import_config,
+            set_category_flags,
+            pin_foreground_app,
+            config_info,
+            config_schema,
+            open_settings,
+            get_accessibility,
+            get_accent_color,
+            get_style,
+            set_style_override,
+            clear_style_override,
+            get_label_mode,
+            get_wifi_ssids,
+            set_label_mode,
+            is_minimal,
+            get_dock_bounds,
+            play_sound,
+            highlight_icon,
+            get_dnd,
+            set_dnd,
             get_icon_base64,
+            refresh_icon,
+            reload_icons,
+            startup_finished,
+            set_bar_rect,
+            icon_tiers,
+            capture_dock,
             launch_app,
+            open_with,
+            confirm_launch,
+            run_action,
+            get_launch_counts,
             get_running_apps,
-            set_dock_hidden
+            get_windowed_apps,
+            get_window_counts,
+            focus_app,
+            close_app,
+            cycle_app_windows,
+            set_dock_hidden,
+            list_monitors,
+            is_app_running,
+            is_safe_mode,
+            rename_shortcut,
+            rename_category,
+            get_new_items,
+            mark_seen,
+            copy_shortcut_path,
+            drag_shortcut_out,
+            quit_dock
         ])
-        .setup(|app| {
-            hide_taskbar();
+        .setup(move |app| {
+            if safe_mode {
+                // Icons and config only: placed once, then left alone
+                let main_window = app.get_webview_window("main").unwrap();
+                let _ = main_window.set_always_on_top(false);
+                update_dock_position(&main_window, &app.state::<Mutex<AppState>>());
+                // Not shell integration: loading the config is what safe mode checks
+                if unavailable {
+                    load_config_when_available(app.handle().clone());
+                }
+                return Ok(());
+            }
+
+            if !minimal {
+                hide_taskbar(app.handle());
+            }
 
             let main_window = app.get_webview_window("main").unwrap();
             let state = app.state::<Mutex<AppState>>();
 
             // Initial positioning
-            update_dock_position(&main_window, &state);
+            profile::time("window setup", || {
+                update_dock_position(&main_window, &state);
+                apply_topmost(app.handle(), &state);
+                apply_backdrop(&main_window, &state);
+            });
+
+            let hide_on_fullscreen = state
+                .lock()
+                .map(|s| s.config.hide_on_fullscreen)
+                .unwrap_or(true);
+            if hide_on_fullscreen {
+                fullscreen::watch(main_window.clone());
+            }
+            clickthrough::watch(main_window.clone());
+            occlusion::watch(main_window.clone());
+
+            if unavailable {
+                load_config_when_available(app.handle().clone());
+            }
 
-            // Listen for changes to handle resolution/scaling automatically
+            if let Ok(hotkeys) = state.lock().map(|s| s.config.hotkeys.clone()) {
+                hotkeys::register(app.handle(), &hotkeys);
+            }
+
+            power::watch(app.handle().clone());
+
+            #[cfg(target_os = "windows")]
+            explorer::watch_shell_changes(&main_window);
+
+            // Listen for changes to handle resolution/scaling automatically.
+            // Each event bumps the generation; only the last one in a burst
+            // survives the debounce sleep and repositions the dock.
             let window_ref = main_window.clone();
-            let state_ref = state.inner().clone();
+            let reposition_gen = Arc::new(AtomicU64::new(0));
             main_window.on_window_event(move |event| match event {
                 WindowEvent::ScaleFactorChanged { .. }
                 | WindowEvent::Moved { .. }
                 | WindowEvent::Resized(..) => {
-                    update_dock_position(&window_ref, state_ref);
+                    let generation = reposition_gen.fetch_add(1, Ordering::SeqCst) + 1;
+                    let gen_ref = reposition_gen.clone();
+                    let window = window_ref.clone();
+                    std::thread::spawn(move || {
+                        std::thread::sleep(REPOSITION_DEBOUNCE);
+                        if gen_ref.load(Ordering::SeqCst) == generation {
+                            let state = window.state::<Mutex<AppState>>();
+                            update_dock_position(&window, &state);
+                        }
+                    });
                 }
                 WindowEvent::Destroyed => {
                     #[cfg(target_os = "windows")]
@@ -301,6 +1862,81 @@ pub fn run() {
 
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .run(tauri::generate_context!());
+
+    if let Err(e) = result {
+        // e.g. WebView2 missing or broken: say so instead of dying silently
+        error!("OxideDock failed to start: {}", e);
+        show_taskbar();
+        show_fatal_error(&format!(
+            "OxideDock could not start:\n\n{}\n\nThe taskbar has been restored.",
+            e
+        ));
+    }
+}
+
+fn show_fatal_error(message: &str) {
+    unsafe {
+        MessageBoxW(
+            HWND::default(),
+            &windows::core::HSTRING::from(message),
+            w!("OxideDock"),
+            MB_OK | MB_ICONERROR,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_with(shortcuts: Vec<config::Shortcut>) -> config::Config {
+        config::Config {
+            categories: vec![config::Category {
+                name: "Apps".to_string(),
+                shortcuts,
+                source: None,
+                discovered: false,
+                enabled: true,
+                collapsed: false,
+                sort: Default::default(),
+            }],
+            ..config::Config::default()
+        }
+    }
+
+    #[test]
+    fn process_pattern_prefers_process_match() {
+        let config = config_with(vec![config::Shortcut {
+            name: "Code".to_string(),
+            path: r"C:\Apps\Code\launcher.exe".to_string(),
+            process_match: Some("code.exe".to_string()),
+            ..Default::default()
+        }]);
+        assert_eq!(
+            process_pattern(&config, r"c:\apps\code\LAUNCHER.EXE".to_string()),
+            "code.exe"
+        );
+    }
+
+    #[test]
+    fn process_pattern_falls_back_to_path() {
+        let config = config_with(vec![config::Shortcut {
+            name: "Notes".to_string(),
+            path: r"C:\Apps\notes.exe".to_string(),
+            ..Default::default()
+        }]);
+        for path in [r"C:\Apps\notes.exe", r"C:\Other\app.exe"] {
+            assert_eq!(process_pattern(&config, path.to_string()), path);
+        }
+    }
+
+    #[test]
+    fn is_running_matches_names_and_paths_ignoring_case() {
+        let running = vec![r"c:\apps\code\code.exe".to_string()];
+        assert!(is_running("Code.exe", &running));
+        assert!(is_running(r"C:\Apps\Code\Code.exe", &running));
+        assert!(!is_running(r"C:\Apps\Code\launcher.exe", &running));
+        assert!(!is_running("", &running));
+    }
 }
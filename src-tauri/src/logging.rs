@@ -0,0 +1,75 @@
+use log::warn;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Env var holding an `env_logger` filter, e.g. `OXIDEDOCK_LOG=debug`.
+const LOG_ENV: &str = "OXIDEDOCK_LOG";
+
+/// The configured log file, once `attach_file` has opened it.
+static FILE: Mutex<Option<File>> = Mutex::new(None);
+
+/// What was logged before the config said where the log file is, so config
+/// loading's warnings end up in the file too. `None` once attached.
+static EARLY: Mutex<Option<Vec<u8>>> = Mutex::new(Some(Vec::new()));
+
+/// Where records go: the log file when there is one, else stderr.
+struct Sink;
+
+impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let Ok(mut file) = FILE.lock() {
+            if let Some(file) = file.as_mut() {
+                return file.write(buf);
+            }
+        }
+        if let Ok(mut early) = EARLY.lock() {
+            if let Some(early) = early.as_mut() {
+                early.extend_from_slice(buf);
+            }
+        }
+        io::stderr().write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if let Ok(mut file) = FILE.lock() {
+            if let Some(file) = file.as_mut() {
+                return file.flush();
+            }
+        }
+        io::stderr().flush()
+    }
+}
+
+/// Install the global logger, writing to stderr until `attach_file`. Defaults
+/// to `info` so per-icon extraction traces (logged at `debug`) stay quiet on
+/// normal runs.
+pub fn init() {
+    let _ = env_logger::Builder::from_env(env_logger::Env::new().filter_or(LOG_ENV, "info"))
+        .target(env_logger::Target::Pipe(Box::new(Sink)))
+        .try_init();
+}
+
+/// Send further records to `log_file` (appended), starting with everything
+/// logged since `init`. Without a file they keep going to stderr.
+pub fn attach_file(log_file: Option<&Path>) {
+    let early = EARLY
+        .lock()
+        .ok()
+        .and_then(|mut early| early.take())
+        .unwrap_or_default();
+    let Some(path) = log_file else {
+        return;
+    };
+    match OpenOptions::new().create(true).append(true).open(path) {
+        Ok(mut file) => {
+            let _ = file.write_all(&early);
+            if let Ok(mut slot) = FILE.lock() {
+                *slot = Some(file);
+            }
+        }
+        Err(e) => warn!("Failed to open log file {:?}: {}", path, e),
+    }
+}
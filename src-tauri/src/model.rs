@@ -0,0 +1,99 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::config::{CategorySort, Config, Shortcut, ShortcutAction};
+use crate::{process, usage};
+
+/// What the dock shows: enabled categories in display order, each shortcut
+/// annotated with its live state.
+#[derive(Debug, Serialize)]
+pub struct DockModel {
+    pub categories: Vec<CategoryModel>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CategoryModel {
+    pub name: String,
+    pub collapsed: bool,
+    pub discovered: bool,
+    pub shortcuts: Vec<ShortcutModel>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShortcutModel {
+    /// `category/target`, stable across reloads as long as neither is renamed
+    pub id: String,
+    #[serde(flatten)]
+    pub shortcut: Shortcut,
+    pub is_running: bool,
+    /// The target file/folder is there (always true for non-file actions)
+    pub exists: bool,
+    /// Added or discovered since, and not clicked yet
+    pub is_new: bool,
+}
+
+/// Build the model from `config`, sorting each category with the launch
+/// `counts` and marking shortcuts whose process is among `running_paths`
+/// and those whose key is in `new_keys`.
+pub fn build(
+    config: &Config,
+    counts: &HashMap<String, u64>,
+    running_paths: &[String],
+    new_keys: &[String],
+) -> DockModel {
+    let categories = config
+        .categories
+        .iter()
+        .filter(|c| c.enabled)
+        .map(|category| {
+            let mut shortcuts = category.shortcuts.clone();
+            sort(&mut shortcuts, category.sort, counts);
+            CategoryModel {
+                name: category.name.clone(),
+                collapsed: category.collapsed,
+                discovered: category.discovered,
+                shortcuts: shortcuts
+                    .into_iter()
+                    .map(|shortcut| ShortcutModel {
+                        id: format!("{}/{}", category.name, usage::key(&shortcut)),
+                        is_running: is_running(&shortcut, running_paths),
+                        exists: target_exists(&shortcut),
+                        is_new: new_keys.contains(&usage::key(&shortcut)),
+                        shortcut,
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+
+    DockModel { categories }
+}
+
+/// Stable, so ties keep the config order.
+fn sort(shortcuts: &mut [Shortcut], order: CategorySort, counts: &HashMap<String, u64>) {
+    match order {
+        CategorySort::Manual => {}
+        CategorySort::NameAsc => shortcuts.sort_by_key(|s| s.name.to_lowercase()),
+        CategorySort::NameDesc => {
+            shortcuts.sort_by_key(|s| std::cmp::Reverse(s.name.to_lowercase()))
+        }
+        CategorySort::FrequencyDesc => shortcuts
+            .sort_by_key(|s| std::cmp::Reverse(counts.get(&usage::key(s)).copied().unwrap_or(0))),
+    }
+}
+
+fn is_running(shortcut: &Shortcut, running_paths: &[String]) -> bool {
+    let pattern = shortcut.process_match.as_deref().unwrap_or(&shortcut.path);
+    !pattern.is_empty()
+        && running_paths
+            .iter()
+            .any(|path| process::matches_process(pattern, path))
+}
+
+fn target_exists(shortcut: &Shortcut) -> bool {
+    match shortcut.action {
+        ShortcutAction::Launch | ShortcutAction::OpenFolder => Path::new(&shortcut.path).exists(),
+        _ => true,
+    }
+}
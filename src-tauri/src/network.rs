@@ -0,0 +1,68 @@
+#[cfg(target_os = "windows")]
+use windows::Win32::Foundation::{ERROR_SUCCESS, HANDLE};
+#[cfg(target_os = "windows")]
+use windows::Win32::NetworkManagement::WiFi::{
+    WLAN_CONNECTION_ATTRIBUTES, WLAN_INTERFACE_INFO_LIST, WlanCloseHandle, WlanEnumInterfaces,
+    WlanFreeMemory, WlanOpenHandle, WlanQueryInterface, wlan_interface_state_connected,
+    wlan_intf_opcode_current_connection,
+};
+
+/// WLAN API version 2 (Vista and later).
+#[cfg(target_os = "windows")]
+const WLAN_CLIENT_VERSION: u32 = 2;
+
+/// SSIDs of the Wi-Fi networks currently connected, one per connected
+/// adapter. Empty without Wi-Fi hardware or the WLAN service.
+#[cfg(target_os = "windows")]
+pub fn connected_ssids() -> Vec<String> {
+    let mut ssids = Vec::new();
+    unsafe {
+        let mut version = 0u32;
+        let mut client = HANDLE::default();
+        if WlanOpenHandle(WLAN_CLIENT_VERSION, None, &mut version, &mut client) != ERROR_SUCCESS.0 {
+            return ssids;
+        }
+
+        let mut list: *mut WLAN_INTERFACE_INFO_LIST = std::ptr::null_mut();
+        if WlanEnumInterfaces(client, None, &mut list) == ERROR_SUCCESS.0 && !list.is_null() {
+            // Variable-length array declared with one element
+            let interfaces = std::slice::from_raw_parts(
+                (*list).InterfaceInfo.as_ptr(),
+                (*list).dwNumberOfItems as usize,
+            );
+            for interface in interfaces {
+                if interface.isState != wlan_interface_state_connected {
+                    continue;
+                }
+                let mut size = 0u32;
+                let mut data: *mut std::ffi::c_void = std::ptr::null_mut();
+                let status = WlanQueryInterface(
+                    client,
+                    &interface.InterfaceGuid,
+                    wlan_intf_opcode_current_connection,
+                    None,
+                    &mut size,
+                    &mut data,
+                    None,
+                );
+                if status != ERROR_SUCCESS.0 || data.is_null() {
+                    continue;
+                }
+                let connection = &*(data as *const WLAN_CONNECTION_ATTRIBUTES);
+                let ssid = &connection.wlanAssociationAttributes.dot11Ssid;
+                let len = (ssid.uSSIDLength as usize).min(ssid.ucSSID.len());
+                // SSIDs are raw bytes; nearly always UTF-8 in practice
+                ssids.push(String::from_utf8_lossy(&ssid.ucSSID[..len]).into_owned());
+                WlanFreeMemory(data);
+            }
+            WlanFreeMemory(list as *const _);
+        }
+        WlanCloseHandle(client, None);
+    }
+    ssids
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn connected_ssids() -> Vec<String> {
+    Vec::new()
+}
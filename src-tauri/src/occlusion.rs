@@ -0,0 +1,96 @@
+use log::debug;
+use std::time::Duration;
+use tauri::Emitter;
+
+#[cfg(target_os = "windows")]
+use windows::Win32::{
+    Foundation::{HWND, RECT},
+    Graphics::Dwm::{DWMWA_CLOAKED, DwmGetWindowAttribute},
+    UI::WindowsAndMessaging::{
+        GW_HWNDPREV, GWL_EXSTYLE, GetWindow, GetWindowLongW, GetWindowRect, IsIconic,
+        IsWindowVisible, WS_EX_LAYERED, WS_EX_TRANSPARENT,
+    },
+};
+
+/// How often the windows above the dock are checked.
+const POLL_INTERVAL: Duration = Duration::from_millis(1000);
+
+/// Tell the frontend ("dock-occluded") when another window covers the whole
+/// dock and when it's visible again, so it can pause magnification and
+/// running-indicator polling nobody can see. Mostly happens when the dock
+/// isn't topmost, or under a topmost fullscreen game.
+pub fn watch(window: tauri::WebviewWindow) {
+    std::thread::spawn(move || {
+        let mut occluded = false;
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+
+            let now = is_occluded(&window);
+            if now == occluded {
+                continue;
+            }
+            occluded = now;
+            debug!("Dock occluded: {}", occluded);
+            let _ = window.emit_to("main", "dock-occluded", occluded);
+        }
+    });
+}
+
+/// Whether a window above the dock in Z-order covers all of it.
+#[cfg(target_os = "windows")]
+fn is_occluded(window: &tauri::WebviewWindow) -> bool {
+    let Ok(hwnd) = window.hwnd() else {
+        return false;
+    };
+    let dock = HWND(hwnd.0 as isize);
+    unsafe {
+        let mut dock_rect = RECT::default();
+        if !IsWindowVisible(dock).as_bool() || GetWindowRect(dock, &mut dock_rect).is_err() {
+            return false;
+        }
+        let mut above = GetWindow(dock, GW_HWNDPREV);
+        while above != HWND::default() {
+            if covers(above, &dock_rect) {
+                return true;
+            }
+            above = GetWindow(above, GW_HWNDPREV);
+        }
+    }
+    false
+}
+
+/// Whether `hwnd` visibly covers `target`. Click-through and layered windows
+/// (game overlays, notifications) are skipped since they may be see-through;
+/// wrongly treating the dock as visible only costs the savings.
+#[cfg(target_os = "windows")]
+unsafe fn covers(hwnd: HWND, target: &RECT) -> bool {
+    if !IsWindowVisible(hwnd).as_bool() || IsIconic(hwnd).as_bool() {
+        return false;
+    }
+    let ex_style = GetWindowLongW(hwnd, GWL_EXSTYLE) as u32;
+    if ex_style & (WS_EX_TRANSPARENT.0 | WS_EX_LAYERED.0) != 0 {
+        return false;
+    }
+    // Cloaked: on another virtual desktop, or a suspended store app
+    let mut cloaked = 0u32;
+    let cloaked_query = DwmGetWindowAttribute(
+        hwnd,
+        DWMWA_CLOAKED,
+        &mut cloaked as *mut u32 as *mut std::ffi::c_void,
+        std::mem::size_of::<u32>() as u32,
+    );
+    if cloaked_query.is_ok() && cloaked != 0 {
+        return false;
+    }
+    let mut rect = RECT::default();
+    GetWindowRect(hwnd, &mut rect).is_ok()
+        && rect.left <= target.left
+        && rect.top <= target.top
+        && rect.right >= target.right
+        && rect.bottom >= target.bottom
+}
+
+#[cfg(not(target_os = "windows"))]
+fn is_occluded(_window: &tauri::WebviewWindow) -> bool {
+    false
+}
@@ -0,0 +1,52 @@
+use crate::AppState;
+use crate::config::MinimalMode;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::Manager;
+
+#[cfg(target_os = "windows")]
+use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+/// How often the power source (and a config change of the mode) is checked.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Whether minimal mode should be in effect for `mode` right now.
+pub fn minimal_active(mode: MinimalMode) -> bool {
+    match mode {
+        MinimalMode::Off => false,
+        MinimalMode::On => true,
+        MinimalMode::OnBattery => on_battery(),
+    }
+}
+
+/// Switch minimal mode on and off as the power source or the configured mode
+/// changes.
+pub fn watch(app: tauri::AppHandle) {
+    std::thread::spawn(move || {
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+
+            let state = app.state::<Mutex<AppState>>();
+            let Ok((mode, minimal)) = state.lock().map(|s| (s.config.minimal_mode, s.minimal))
+            else {
+                continue;
+            };
+            let wanted = minimal_active(mode);
+            if wanted != minimal {
+                crate::set_minimal(&app, wanted);
+            }
+        }
+    });
+}
+
+/// Running off the battery. An unknown AC line status counts as plugged in.
+#[cfg(target_os = "windows")]
+fn on_battery() -> bool {
+    let mut status = SYSTEM_POWER_STATUS::default();
+    unsafe { GetSystemPowerStatus(&mut status).is_ok() && status.ACLineStatus == 0 }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn on_battery() -> bool {
+    false
+}
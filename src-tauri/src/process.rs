@@ -0,0 +1,393 @@
+#[cfg(target_os = "windows")]
+use log::debug;
+#[cfg(target_os = "windows")]
+use std::sync::atomic::{AtomicIsize, Ordering};
+#[cfg(target_os = "windows")]
+use windows::Win32::{
+    Foundation::{
+        BOOL, CloseHandle, ERROR_INVALID_PARAMETER, HANDLE, HWND, LPARAM, WAIT_OBJECT_0, WPARAM,
+    },
+    System::ProcessStatus::EnumProcesses,
+    System::Threading::{
+        OpenProcess, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION, PROCESS_SYNCHRONIZE,
+        PROCESS_TERMINATE, QueryFullProcessImageNameW, TerminateProcess, WaitForSingleObject,
+    },
+    UI::WindowsAndMessaging::{
+        EnumWindows, GW_OWNER, GWL_EXSTYLE, GetForegroundWindow, GetWindow, GetWindowLongW,
+        GetWindowThreadProcessId, IsIconic, IsWindowVisible, PostMessageW, SW_MINIMIZE, SW_RESTORE,
+        SetForegroundWindow, ShowWindow, WM_CLOSE, WS_EX_TOOLWINDOW,
+    },
+};
+
+/// Match a process image path against an image name (`app.exe`) or a full
+/// path, case-insensitively.
+pub fn matches_process(pattern: &str, image_path: &str) -> bool {
+    if pattern.contains(['\\', '/']) {
+        pattern.eq_ignore_ascii_case(image_path)
+    } else {
+        std::path::Path::new(image_path)
+            .file_name()
+            .and_then(|s| s.to_str())
+            .is_some_and(|name| name.eq_ignore_ascii_case(pattern))
+    }
+}
+
+/// Lowercased image paths of every process we can query.
+pub fn running_app_paths() -> Vec<String> {
+    #[cfg(target_os = "windows")]
+    {
+        let mut paths = std::collections::HashSet::new();
+        for pid in process_ids() {
+            if pid == 0 {
+                continue;
+            }
+            if let Some(path) = process_image_path(pid) {
+                paths.insert(path);
+            }
+        }
+        return paths.into_iter().collect();
+    }
+    #[cfg(not(target_os = "windows"))]
+    vec![]
+}
+
+/// Every process id, growing the buffer until EnumProcesses has room to spare.
+#[cfg(target_os = "windows")]
+fn process_ids() -> Vec<u32> {
+    process_ids_from(1024)
+}
+
+#[cfg(target_os = "windows")]
+fn process_ids_from(capacity: usize) -> Vec<u32> {
+    let mut pids = vec![0u32; capacity.max(1)];
+    loop {
+        let size = (pids.len() * std::mem::size_of::<u32>()) as u32;
+        let mut cb_needed = 0u32;
+        if unsafe { EnumProcesses(pids.as_mut_ptr(), size, &mut cb_needed) }.is_err() {
+            return Vec::new();
+        }
+        // EnumProcesses can't report how many it left out; a full buffer
+        // means the list may have been cut short
+        if cb_needed < size {
+            pids.truncate(cb_needed as usize / std::mem::size_of::<u32>());
+            return pids;
+        }
+        debug!("More than {} processes, growing the PID buffer", pids.len());
+        pids.resize(pids.len() * 2, 0);
+    }
+}
+
+/// Lowercased image paths of processes that own a taskbar-style window,
+/// i.e. the apps a user would consider "open".
+pub fn windowed_app_paths() -> Vec<String> {
+    let mut paths: Vec<String> = Vec::new();
+    #[cfg(target_os = "windows")]
+    for (_, path) in app_windows() {
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+    paths
+}
+
+/// Number of taskbar-style windows per lowercased image path.
+pub fn window_counts() -> std::collections::HashMap<String, usize> {
+    let mut counts = std::collections::HashMap::new();
+    #[cfg(target_os = "windows")]
+    for (_, path) in app_windows() {
+        *counts.entry(path).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Bring the top-most window belonging to `path` (see [`matches_process`])
+/// to the foreground. Returns `false` when no such window exists.
+pub fn focus_app(path: &str) -> bool {
+    #[cfg(target_os = "windows")]
+    if let Some((hwnd, _)) = app_windows()
+        .into_iter()
+        .find(|(_, p)| matches_process(path, p))
+    {
+        return activate_window(hwnd);
+    }
+    #[cfg(not(target_os = "windows"))]
+    let _ = path;
+    false
+}
+
+/// Activate the next (or previous) window of `path` after the one currently
+/// in the foreground. Windows are cycled in a stable order so repeated calls
+/// visit each of them in turn.
+pub fn cycle_app_windows(path: &str, forward: bool) -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        let mut hwnds: Vec<HWND> = app_windows()
+            .into_iter()
+            .filter(|(_, p)| matches_process(path, p))
+            .map(|(hwnd, _)| hwnd)
+            .collect();
+        if hwnds.is_empty() {
+            return false;
+        }
+        hwnds.sort_by_key(|hwnd| hwnd.0);
+
+        let foreground = unsafe { GetForegroundWindow() };
+        let next = match hwnds.iter().position(|&h| h == foreground) {
+            Some(i) if forward => (i + 1) % hwnds.len(),
+            Some(i) => (i + hwnds.len() - 1) % hwnds.len(),
+            None => 0,
+        };
+        return activate_window(hwnds[next]);
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = (path, forward);
+        false
+    }
+}
+
+/// The window that had focus before the dock was last activated. Clicking the
+/// dock activates it, so by the time a click arrives this is the window the
+/// user was working in.
+#[cfg(target_os = "windows")]
+static PREVIOUS_FOREGROUND: AtomicIsize = AtomicIsize::new(0);
+
+/// Record the window the dock took focus from (WM_ACTIVATE's lParam).
+#[cfg(target_os = "windows")]
+pub fn note_activated_from(hwnd: HWND) {
+    PREVIOUS_FOREGROUND.store(hwnd.0, Ordering::Relaxed);
+}
+
+/// Whether `path` (see [`matches_process`]) owned the focused window when the
+/// dock was clicked. A minimized window no longer counts.
+pub fn was_foreground(path: &str) -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        let previous = HWND(PREVIOUS_FOREGROUND.load(Ordering::Relaxed));
+        let foreground = unsafe { GetForegroundWindow() };
+        let mut pid = 0u32;
+        unsafe { GetWindowThreadProcessId(foreground, Some(&mut pid)) };
+        // Focus moved elsewhere since the dock was activated
+        if foreground != previous && pid != std::process::id() {
+            return false;
+        }
+        if previous == HWND::default() || unsafe { IsIconic(previous) }.as_bool() {
+            return false;
+        }
+        return app_windows()
+            .into_iter()
+            .any(|(hwnd, p)| hwnd == previous && matches_process(path, &p));
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = path;
+        false
+    }
+}
+
+/// Minimize every window of `path`, or with `others` every window of all
+/// other apps.
+pub fn minimize_windows(path: &str, others: bool) {
+    #[cfg(target_os = "windows")]
+    for (hwnd, _) in app_windows()
+        .into_iter()
+        .filter(|(_, p)| matches_process(path, p) != others)
+    {
+        unsafe {
+            if !IsIconic(hwnd).as_bool() {
+                ShowWindow(hwnd, SW_MINIMIZE);
+            }
+        }
+    }
+    #[cfg(not(target_os = "windows"))]
+    let _ = (path, others);
+}
+
+/// How a `close_app` request ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CloseOutcome {
+    /// Every process exited on its own after WM_CLOSE
+    Exited,
+    /// At least one process ignored WM_CLOSE and was terminated
+    ForceKilled,
+    /// Something is still running (e.g. an unsaved-changes prompt) and
+    /// `force` wasn't set, or a process couldn't be opened to watch it
+    /// (elevated, or another user's)
+    StillRunning,
+}
+
+/// Ask every window of `path` to close, then wait up to `grace` for the owning
+/// processes to exit. Stragglers are terminated only with `force`. `None` when
+/// the app has no windows.
+pub fn close_app(path: &str, grace: std::time::Duration, force: bool) -> Option<CloseOutcome> {
+    #[cfg(target_os = "windows")]
+    {
+        let windows: Vec<HWND> = app_windows()
+            .into_iter()
+            .filter(|(_, p)| matches_process(path, p))
+            .map(|(hwnd, _)| hwnd)
+            .collect();
+        if windows.is_empty() {
+            return None;
+        }
+        let mut pids: Vec<u32> = Vec::new();
+        for &hwnd in &windows {
+            let mut pid = 0u32;
+            unsafe { GetWindowThreadProcessId(hwnd, Some(&mut pid)) };
+            if !pids.contains(&pid) {
+                pids.push(pid);
+            }
+        }
+
+        // Opened before WM_CLOSE goes out, so a process that exits straight
+        // away isn't mistaken for one we may not open
+        let access = if force {
+            PROCESS_SYNCHRONIZE | PROCESS_TERMINATE
+        } else {
+            PROCESS_SYNCHRONIZE
+        };
+        let mut outcome = CloseOutcome::Exited;
+        let mut handles: Vec<HANDLE> = Vec::new();
+        for pid in pids {
+            match unsafe { OpenProcess(access, false, pid) } {
+                Ok(handle) => handles.push(handle),
+                // The process is already gone
+                Err(e) if e.code() == ERROR_INVALID_PARAMETER.to_hresult() => {}
+                // Elevated or another user's: it can't be watched, so it
+                // can't be reported as closed
+                Err(e) => {
+                    debug!("Can't wait on process {}: {}", pid, e);
+                    outcome = CloseOutcome::StillRunning;
+                }
+            }
+        }
+        for hwnd in windows {
+            unsafe {
+                let _ = PostMessageW(hwnd, WM_CLOSE, WPARAM(0), LPARAM(0));
+            }
+        }
+
+        let deadline = std::time::Instant::now() + grace;
+        for handle in handles {
+            let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+            let timeout = remaining.as_millis().min(u32::MAX as u128) as u32;
+            unsafe {
+                if WaitForSingleObject(handle, timeout) != WAIT_OBJECT_0 {
+                    if force && TerminateProcess(handle, 1).is_ok() {
+                        if outcome == CloseOutcome::Exited {
+                            outcome = CloseOutcome::ForceKilled;
+                        }
+                    } else {
+                        outcome = CloseOutcome::StillRunning;
+                    }
+                }
+                let _ = CloseHandle(handle);
+            }
+        }
+        return Some(outcome);
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = (path, grace, force);
+        None
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn activate_window(hwnd: HWND) -> bool {
+    unsafe {
+        if IsIconic(hwnd).as_bool() {
+            ShowWindow(hwnd, SW_RESTORE);
+        }
+        SetForegroundWindow(hwnd).as_bool()
+    }
+}
+
+/// Full image path (as reported, not lowercased) of the process owning the
+/// foreground window, unless that's the dock itself.
+pub fn foreground_app_path() -> Option<String> {
+    #[cfg(target_os = "windows")]
+    {
+        let mut pid = 0u32;
+        unsafe { GetWindowThreadProcessId(GetForegroundWindow(), Some(&mut pid)) };
+        if pid == 0 || pid == std::process::id() {
+            return None;
+        }
+        return query_image_path(pid);
+    }
+    #[cfg(not(target_os = "windows"))]
+    None
+}
+
+#[cfg(target_os = "windows")]
+fn process_image_path(pid: u32) -> Option<String> {
+    query_image_path(pid).map(|path| path.to_lowercase())
+}
+
+#[cfg(target_os = "windows")]
+fn query_image_path(pid: u32) -> Option<String> {
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+        let mut buffer = [0u16; 1024];
+        let mut size = buffer.len() as u32;
+        let result = QueryFullProcessImageNameW(
+            handle,
+            PROCESS_NAME_WIN32,
+            windows::core::PWSTR(buffer.as_mut_ptr()),
+            &mut size,
+        );
+        let _ = CloseHandle(handle);
+        result.ok()?;
+        Some(String::from_utf16_lossy(&buffer[..size as usize]))
+    }
+}
+
+/// Visible, unowned, non-tool top-level windows in Z-order (top first),
+/// paired with their process image path.
+#[cfg(target_os = "windows")]
+fn app_windows() -> Vec<(HWND, String)> {
+    unsafe extern "system" fn collect(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        let windows = &mut *(lparam.0 as *mut Vec<HWND>);
+        let ex_style = GetWindowLongW(hwnd, GWL_EXSTYLE) as u32;
+        if IsWindowVisible(hwnd).as_bool()
+            && GetWindow(hwnd, GW_OWNER) == HWND::default()
+            && ex_style & WS_EX_TOOLWINDOW.0 == 0
+        {
+            windows.push(hwnd);
+        }
+        BOOL(1)
+    }
+
+    let mut hwnds: Vec<HWND> = Vec::new();
+    unsafe {
+        let _ = EnumWindows(Some(collect), LPARAM(&mut hwnds as *mut Vec<HWND> as isize));
+    }
+
+    hwnds
+        .into_iter()
+        .filter_map(|hwnd| {
+            let mut pid = 0u32;
+            unsafe { GetWindowThreadProcessId(hwnd, Some(&mut pid)) };
+            // The dock's own window is not an "app" for these purposes
+            if pid == std::process::id() {
+                return None;
+            }
+            process_image_path(pid).map(|path| (hwnd, path))
+        })
+        .collect()
+}
+
+#[cfg(all(test, windows))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn process_ids_grow_past_a_full_buffer() {
+        // Far more processes than one slot: only the growth path can see
+        // them all, including this one
+        let pids = process_ids_from(1);
+        assert!(pids.len() > 1);
+        assert!(pids.contains(&std::process::id()));
+    }
+}
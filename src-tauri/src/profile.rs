@@ -0,0 +1,77 @@
+use log::debug;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// When `run` started, the zero point for first paint.
+static STARTED: OnceLock<Instant> = OnceLock::new();
+
+/// First paint has been reported; later summaries come from page reloads.
+static PAINTED: AtomicBool = AtomicBool::new(false);
+
+/// Startup phases in the order they finished, buffered for the one summary
+/// logged at first paint.
+static PHASES: Mutex<Vec<(&'static str, Duration)>> = Mutex::new(Vec::new());
+
+/// Icons extracted since the last summary, and the time spent on them.
+static ICONS: Mutex<(u32, Duration)> = Mutex::new((0, Duration::ZERO));
+
+pub fn start() {
+    let _ = STARTED.set(Instant::now());
+}
+
+/// Run `f`, recording how long it took as `phase`.
+pub fn time<T>(phase: &'static str, f: impl FnOnce() -> T) -> T {
+    let started = Instant::now();
+    let result = f();
+    if let Ok(mut phases) = PHASES.lock() {
+        phases.push((phase, started.elapsed()));
+    }
+    result
+}
+
+/// Count one icon extraction towards the summary.
+pub fn record_icon(path: &str, elapsed: Duration) {
+    debug!("Icon '{}' extracted in {:?}", path, elapsed);
+    if let Ok(mut icons) = ICONS.lock() {
+        icons.0 += 1;
+        icons.1 += elapsed;
+    }
+}
+
+/// Log where startup time went, at debug level, once the dock has painted.
+/// Later calls (page reloads) report only what happened since, e.g. a config
+/// reload and its icons.
+pub fn log_summary() {
+    // Drained even when not logged: every config load keeps adding phases
+    let phases = PHASES
+        .lock()
+        .map(|mut phases| std::mem::take(&mut *phases))
+        .unwrap_or_default();
+    let (count, total) = ICONS
+        .lock()
+        .map(|mut icons| std::mem::replace(&mut *icons, (0, Duration::ZERO)))
+        .unwrap_or_default();
+    if !log::log_enabled!(log::Level::Debug) {
+        return;
+    }
+    let first_paint = STARTED.get().map(Instant::elapsed).unwrap_or_default();
+
+    let mut summary = String::from("Startup timings:");
+    for (phase, elapsed) in phases {
+        summary.push_str(&format!("\n  {:<20} {:>8.1?}", phase, elapsed));
+    }
+    let average = if count > 0 {
+        total / count
+    } else {
+        Duration::ZERO
+    };
+    summary.push_str(&format!(
+        "\n  {:<20} {:>8.1?} ({} icons, {:.1?} each)",
+        "icon extraction", total, count, average
+    ));
+    if !PAINTED.swap(true, Ordering::Relaxed) {
+        summary.push_str(&format!("\n  {:<20} {:>8.1?}", "first paint", first_paint));
+    }
+    debug!("{}", summary);
+}
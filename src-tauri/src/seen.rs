@@ -0,0 +1,70 @@
+use log::warn;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::{Config, Shortcut};
+use crate::usage;
+
+/// Shortcuts the user has clicked at least once, keyed as in `usage::key`
+/// and kept in a JSON file next to the config. Anything else on the dock is
+/// flagged as new.
+pub struct SeenStore {
+    path: PathBuf,
+    seen: HashSet<String>,
+}
+
+impl SeenStore {
+    /// Load the store from `path`. Without a file yet (first run, or the
+    /// first run of a version that tracks this), everything in `config`
+    /// counts as seen, so only later additions show up as new.
+    pub fn load(path: PathBuf, config: &Config) -> Self {
+        let stored: Option<HashSet<String>> = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok());
+        match stored {
+            Some(seen) => Self { path, seen },
+            None => {
+                let seen = shortcuts(config).map(usage::key).collect();
+                save(&path, &seen);
+                Self { path, seen }
+            }
+        }
+    }
+
+    pub fn is_new(&self, shortcut: &Shortcut) -> bool {
+        !self.seen.contains(&usage::key(shortcut))
+    }
+
+    /// Keys of the shortcuts in `config` that haven't been clicked yet.
+    pub fn new_keys(&self, config: &Config) -> Vec<String> {
+        shortcuts(config)
+            .filter(|shortcut| self.is_new(shortcut))
+            .map(usage::key)
+            .collect()
+    }
+
+    /// Remember `key` as seen. When that changed anything, returns the file
+    /// and the keys to hand to [`save`] once the app state lock is released.
+    pub fn mark(&mut self, key: String) -> Option<(PathBuf, HashSet<String>)> {
+        self.seen
+            .insert(key)
+            .then(|| (self.path.clone(), self.seen.clone()))
+    }
+}
+
+/// Write the seen keys to `path`, sorted so the file diffs cleanly.
+pub fn save(path: &Path, seen: &HashSet<String>) {
+    let mut keys: Vec<&String> = seen.iter().collect();
+    keys.sort();
+    let result = serde_json::to_string_pretty(&keys)
+        .map_err(|e| e.to_string())
+        .and_then(|json| fs::write(path, json).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        warn!("Failed to save seen shortcuts to {:?}: {}", path, e);
+    }
+}
+
+fn shortcuts(config: &Config) -> impl Iterator<Item = &Shortcut> {
+    config.categories.iter().flat_map(|c| c.shortcuts.iter())
+}
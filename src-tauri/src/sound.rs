@@ -0,0 +1,38 @@
+use log::warn;
+use std::path::Path;
+
+#[cfg(target_os = "windows")]
+use windows::{
+    Win32::Foundation::HMODULE,
+    Win32::Media::Audio::{PlaySoundW, SND_ASYNC, SND_FILENAME, SND_NODEFAULT},
+    core::HSTRING,
+};
+
+/// Play a .wav file without blocking. A missing file is skipped (rather than
+/// falling back to the system beep) and reported as `false`.
+pub fn play(path: &str) -> bool {
+    if !Path::new(path).exists() {
+        return false;
+    }
+    #[cfg(target_os = "windows")]
+    unsafe {
+        PlaySoundW(
+            &HSTRING::from(path),
+            HMODULE::default(),
+            SND_ASYNC | SND_FILENAME | SND_NODEFAULT,
+        )
+        .as_bool()
+    }
+    #[cfg(not(target_os = "windows"))]
+    false
+}
+
+/// Log configured sounds that don't exist, once at startup instead of on
+/// every hover.
+pub fn warn_missing(sounds: &[Option<&str>]) {
+    for path in sounds.iter().flatten() {
+        if !Path::new(path).exists() {
+            warn!("Sound file not found, it will not play: {}", path);
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use windows::{
+    Win32::{
+        Foundation::{GlobalFree, HANDLE, HWND},
+        System::{
+            Com::IDataObject,
+            DataExchange::{CloseClipboard, EmptyClipboard, OpenClipboard, SetClipboardData},
+            Memory::{GMEM_MOVEABLE, GlobalAlloc, GlobalLock, GlobalUnlock},
+            Ole::{CF_UNICODETEXT, DROPEFFECT_COPY, DROPEFFECT_LINK},
+        },
+        UI::Shell::{BHID_DataObject, IShellItem, SHCreateItemFromParsingName, SHDoDragDrop},
+    },
+    core::HSTRING,
+};
+
+/// Put `text` on the clipboard as Unicode text, owned by `hwnd` (with no
+/// owner, Windows refuses the data).
+pub fn copy_text(hwnd: HWND, text: &str) -> windows::core::Result<()> {
+    let wide: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+    unsafe {
+        OpenClipboard(hwnd)?;
+        let result = (|| {
+            EmptyClipboard()?;
+            let memory = GlobalAlloc(GMEM_MOVEABLE, wide.len() * std::mem::size_of::<u16>())?;
+            let locked = GlobalLock(memory) as *mut u16;
+            if locked.is_null() {
+                let _ = GlobalFree(memory);
+                return Err(windows::core::Error::from_win32());
+            }
+            std::ptr::copy_nonoverlapping(wide.as_ptr(), locked, wide.len());
+            // "Fails" with NO_ERROR once the lock count is back to zero
+            let _ = GlobalUnlock(memory);
+            // On success the clipboard owns the memory
+            if let Err(e) = SetClipboardData(CF_UNICODETEXT.0 as u32, HANDLE(memory.0 as isize)) {
+                let _ = GlobalFree(memory);
+                return Err(e);
+            }
+            Ok(())
+        })();
+        let _ = CloseClipboard();
+        result
+    }
+}
+
+/// Drag `path` out of the dock as a file, using the shell's own data object
+/// (CF_HDROP and the rest), so Explorer, mail clients and editors all accept
+/// it. Runs the modal OLE drag loop on the calling (UI) thread until the
+/// drop or cancel.
+pub fn drag_file(hwnd: HWND, path: &str) -> windows::core::Result<()> {
+    unsafe {
+        let item: IShellItem = SHCreateItemFromParsingName(&HSTRING::from(path), None)?;
+        let data: IDataObject = item.BindToHandler(None, &BHID_DataObject)?;
+        // No drop source of our own: the shell supplies the default one
+        SHDoDragDrop(hwnd, &data, None, DROPEFFECT_COPY | DROPEFFECT_LINK)?;
+    }
+    Ok(())
+}
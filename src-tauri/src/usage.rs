@@ -0,0 +1,54 @@
+use log::warn;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::Shortcut;
+
+/// Per-shortcut launch counts, kept in a JSON file next to the config.
+pub struct UsageStore {
+    path: PathBuf,
+    counts: HashMap<String, u64>,
+}
+
+impl UsageStore {
+    /// Load the store from `path`; a missing or unreadable file starts empty.
+    pub fn load(path: PathBuf) -> Self {
+        let counts = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self { path, counts }
+    }
+
+    pub fn counts(&self) -> &HashMap<String, u64> {
+        &self.counts
+    }
+
+    /// Count one launch of `shortcut`. Returns the file and the counts to
+    /// hand to [`save`] once the caller has released the app state lock.
+    pub fn record(&mut self, shortcut: &Shortcut) -> (PathBuf, HashMap<String, u64>) {
+        *self.counts.entry(key(shortcut)).or_insert(0) += 1;
+        (self.path.clone(), self.counts.clone())
+    }
+}
+
+/// Write launch counts to `path`, logging rather than failing the launch.
+pub fn save(path: &Path, counts: &HashMap<String, u64>) {
+    let result = serde_json::to_string_pretty(counts)
+        .map_err(|e| e.to_string())
+        .and_then(|json| fs::write(path, json).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        warn!("Failed to save launch counts to {:?}: {}", path, e);
+    }
+}
+
+/// Shortcuts are identified by their lowercased target, or by name for
+/// path-less ones (commands, launch groups). The frontend mirrors this.
+pub fn key(shortcut: &Shortcut) -> String {
+    if shortcut.path.is_empty() {
+        shortcut.name.to_lowercase()
+    } else {
+        shortcut.path.to_lowercase()
+    }
+}
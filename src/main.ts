@@ -1,21 +1,89 @@
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { Menu } from "@tauri-apps/api/menu";
+import { getCurrentWebview } from "@tauri-apps/api/webview";
 
 // ═══════════════════════════════════════════════════════════
 // OxideDock — macOS Dock Magnification Engine
 // ═══════════════════════════════════════════════════════════
 
+type ShortcutAction =
+  | { type: "launch" }
+  | { type: "command"; cmd: string; args?: string[] }
+  | { type: "open_folder" }
+  | { type: "url" }
+  | { type: "launch_group"; apps: string[]; delays_ms?: number[] }
+  | { type: "builtin"; name: string };
+
+interface VisibleWhen {
+  hours?: string;
+  days?: string;
+  ssid?: string;
+}
+
 interface Shortcut {
   name: string;
   path: string;
+  process_match?: string;
+  action?: ShortcutAction;
+  single_instance?: boolean;
+  run_as_admin?: boolean;
+  confirm?: string;
+  icon?: string;
+  icon_index?: number;
+  visible_when?: VisibleWhen;
 }
 
+type CategorySort = "manual" | "name_asc" | "name_desc" | "frequency_desc";
+
 interface Category {
   name: string;
   shortcuts: Shortcut[];
+  enabled?: boolean;
+  collapsed?: boolean;
+  sort?: CategorySort;
+}
+
+type LabelMode = "icons_only" | "icons_and_labels";
+type DockTheme = "dark" | "light";
+
+interface DockStyle {
+  icon_shape?: "square" | "rounded" | "circle";
+  theme?: DockTheme;
+  label_mode?: LabelMode;
+  font_family?: string;
+  font_size?: number;
+  label_max_width?: number;
+  background_opacity?: number;
+  compact_scale?: number;
+  accent_color?: string;
+  hit_padding?: number;
+  tilt_effect?: boolean;
+  reflections?: boolean;
+}
+
+interface SystemItem {
+  type: "settings" | "toggle_theme" | "clock" | string;
 }
 
 interface Config {
   categories: Category[];
+  style?: DockStyle;
+  system_items?: SystemItem[];
+  show_running_apps?: boolean;
+  recently_closed?: { size?: number; expire_minutes?: number };
+  running_poll_ms?: number;
+  launch_sound?: string;
+  hover_sound?: string;
+  launch_error_toast?: boolean;
+  animation_fps?: number;
+  idle_hide_secs?: number;
+  idle_quit_secs?: number;
+}
+
+interface AccessibilityModes {
+  reduced_motion: boolean;
+  high_contrast: boolean;
 }
 
 // ─── Magnification parameters (macOS-faithful) ───
@@ -24,8 +92,23 @@ const MAX_BASE_SIZE = 64;     // Maximum icon size
 const MIN_BASE_SIZE = 24;     // Minimum icon size before overflow
 const MAX_SCALE = 1.65;       // Maximum magnification
 const MAGNIFY_RANGE = 200;    // Pixels of influence from cursor
+const MAX_TILT_DEG = 4;       // Icon row tilt toward the cursor at the bar's ends
 const LERP_SPEED = 0.18;      // Smooth interpolation factor
 const SPRING_SPEED = 0.14;    // Return-to-rest spring speed
+const WHEEL_CYCLE_INTERVAL = 150; // ms between window switches while scrolling
+const LAUNCH_SPINNER_TIMEOUT = 15000; // ms before giving up on a launch showing up
+const RESORT_DELAY = 1000;     // ms for the bounce before a frequency re-sort rebuilds
+const FULL_LABEL_DELAY = 1200; // ms of hovering before a truncated label shows in full
+const RING_PULSE_DURATION = 250; // ms the selection ring flashes on click
+const DRAG_OUT_THRESHOLD = 24; // px an icon is pulled up before it drags out as a file
+const ENTER_RAMP_MS = 150;     // ms for magnification to reach full strength on entering
+const LAUNCH_FAILED_DURATION = 600; // ms the icon shakes after a failed launch
+const TOAST_DURATION = 4000;   // ms a message stays on the dock
+const REVEAL_TIMEOUT = 800;    // ms to wait for icons before fading the dock in anyway
+const MAX_WINDOW_DOTS = 3;    // Running-indicator dots; more windows show as "3+"
+const VISIBILITY_CHECK_INTERVAL = 60000; // ms between re-checks of visible_when conditions
+const MIN_RUNNING_POLL_MS = 250; // Floor for running_poll_ms; lower would spin the process scan
+const SYSTEM_FONT_STACK = "'Segoe UI', -apple-system, BlinkMacSystemFont, sans-serif";
 
 // ─── State ───
 let dockBarEl: HTMLElement | null = null;
@@ -38,6 +121,41 @@ let targetScales: number[] = [];
 let isAutoHideEnabled = true;
 let isHidden = false;
 let hideDelayTimer: number | null = null;
+let reducedMotion = false;
+let minimalMode = false;
+let safeMode = false; // --safe: static dock, no polling or auto-hide
+let showRunningApps = false;
+let runningPollMs: number | undefined;
+let transientSepEl: HTMLElement | null = null;
+let recentSize = 0;
+let recentExpireMs = 10 * 60 * 1000;
+let recentSepEl: HTMLElement | null = null;
+let systemStripEl: HTMLElement | null = null; // utility items, always last
+let theme: DockTheme = "dark";
+// Recently closed pinned apps by usage key, newest last
+const recentlyClosed = new Map<string, { shortcut: Shortcut; closedAt: number; itemEl: HTMLElement }>();
+let lastWheelCycle = 0;
+let launchCounts: Record<string, number> = {};
+let newItems = new Set<string>(); // usageKey of shortcuts not clicked yet
+let occluded = false; // another window covers the whole dock
+let restScale = 1; // < 1 in compact mode: icons shrink while the dock is unused
+let launchSound = false;
+let launchErrorToast = true;
+let hoverSound = false;
+let hoveredIndex = -1;
+let tiltEffect = false;
+let scalesDirty = false; // cursor moved since the last frame
+let hoverStartTime = 0; // when the cursor last entered the bar
+let frameInterval = 0;   // ms between animation frames; 0 follows the display
+let lastFrameTime = 0;
+let dnd = false; // do not disturb: no bounces, highlight pulses or sounds
+let dropTargetEl: HTMLElement | null = null;
+let iconLoads: Promise<unknown>[] = []; // startup icon extractions the fade-in waits on
+// Shortcut behind each icon that files can be dropped on
+const dropShortcuts = new WeakMap<HTMLElement, Shortcut>();
+// Icons with visible_when; hidden ones stay in place, out of dockItems
+const conditionalItems: { itemEl: HTMLElement; when: VisibleWhen }[] = [];
+let lastSsids: string[] = [];
 
 function calculateBaseSize(itemCount: number) {
   const horizontalPadding = 40; // dock-bar total horizontal padding/margins
@@ -45,7 +163,8 @@ function calculateBaseSize(itemCount: number) {
   const availableWidth = window.innerWidth - horizontalPadding;
 
   const totalGaps = Math.max(0, (itemCount - 1) * gap);
-  let size = (availableWidth - totalGaps) / itemCount;
+  // An empty dock keeps the full size rather than dividing by zero
+  let size = itemCount > 0 ? (availableWidth - totalGaps) / itemCount : MAX_BASE_SIZE;
 
   size = Math.min(MAX_BASE_SIZE, Math.max(MIN_BASE_SIZE, size));
   currentBaseSize = size;
@@ -59,10 +178,21 @@ function gaussian(dist: number): number {
   return Math.exp(-(dist * dist) / (2 * sigma * sigma));
 }
 
+// Magnification strength, easing from 0 to 1 just after the cursor enters
+// so the icon under it grows instead of popping
+function enterRamp(): number {
+  if (reducedMotion) return 1;
+  const t = Math.min(1, (performance.now() - hoverStartTime) / ENTER_RAMP_MS);
+  return t * t * (3 - 2 * t);
+}
+
 function updateTargetScales() {
   if (!dockBarEl) return;
 
   const barRect = dockBarEl.getBoundingClientRect();
+  const strength = enterRamp();
+  let nearest = -1;
+  let nearestDist = Infinity;
 
   for (let i = 0; i < dockItems.length; i++) {
     const item = dockItems[i];
@@ -71,19 +201,66 @@ function updateTargetScales() {
 
     if (isHovering && mouseX > barRect.left - 40 && mouseX < barRect.right + 40) {
       const dist = Math.abs(mouseX - itemCenterX);
-      targetScales[i] = 1 + (MAX_SCALE - 1) * gaussian(dist);
+      targetScales[i] = 1 + (MAX_SCALE - 1) * gaussian(dist) * strength;
+      if (dist < nearestDist) {
+        nearest = i;
+        nearestDist = dist;
+      }
     } else {
-      targetScales[i] = 1;
+      targetScales[i] = restScale;
     }
   }
+
+  if (tiltEffect) {
+    // Cursor offset from the bar's centre, -1 (left end) to 1 (right end)
+    const halfWidth = barRect.width / 2;
+    const offset = isHovering
+      ? Math.max(-1, Math.min(1, (mouseX - (barRect.left + halfWidth)) / halfWidth))
+      : 0;
+    dockBarEl.style.transform = `perspective(800px) rotateY(${offset * MAX_TILT_DEG}deg)`;
+  }
+
+  // Magnification centred on a new icon
+  if (nearest !== hoveredIndex) {
+    hoveredIndex = nearest;
+    if (nearest !== -1 && hoverSound) playSound("hover");
+  }
 }
 
-function applyScales() {
+// Only the bar's vertical band counts as "on the dock"; the transparent
+// area above it is just cursor transit and shouldn't trigger magnification.
+function isWithinBarY(clientY: number): boolean {
+  if (!dockBarEl) return false;
+  const barRect = dockBarEl.getBoundingClientRect();
+  return clientY >= barRect.top && clientY <= barRect.bottom;
+}
+
+function applyScales(time: number) {
+  // Frame-rate cap (config: animation_fps); skip this vsync tick
+  if (frameInterval > 0 && time - lastFrameTime < frameInterval - 1) {
+    animFrameId = requestAnimationFrame(applyScales);
+    return;
+  }
+  lastFrameTime = time;
+
+  // Mouse moves since the last frame collapse into one update at the
+  // latest cursor position
   let needsFrame = false;
+  if (scalesDirty) {
+    scalesDirty = false;
+    updateTargetScales();
+    needsFrame = true;
+  }
+  // Still ramping up: keep growing even if the cursor rests
+  if (isHovering && enterRamp() < 1) {
+    scalesDirty = true;
+    needsFrame = true;
+  }
 
   for (let i = 0; i < dockItems.length; i++) {
     const diff = targetScales[i] - currentScales[i];
-    const speed = isHovering ? LERP_SPEED : SPRING_SPEED;
+    // Reduced motion: snap straight to the target instead of easing
+    const speed = reducedMotion ? 1 : isHovering ? LERP_SPEED : SPRING_SPEED;
 
     if (Math.abs(diff) > 0.001) {
       currentScales[i] += diff * speed;
@@ -114,8 +291,15 @@ function applyScales() {
   }
 }
 
+// Defer the magnification update to the next frame instead of doing the
+// layout reads on every mousemove
+function queueScaleUpdate() {
+  scalesDirty = true;
+  startAnimation();
+}
+
 function startAnimation() {
-  if (animFrameId === null) {
+  if (animFrameId === null && !occluded) {
     animFrameId = requestAnimationFrame(applyScales);
   }
 }
@@ -125,15 +309,88 @@ async function bootstrap() {
   dockBarEl = document.getElementById("dock-bar");
   if (!dockBarEl) return;
 
+  try {
+    const a11y: AccessibilityModes = await invoke("get_accessibility");
+    reducedMotion = a11y.reduced_motion;
+    document.body.classList.toggle("reduced-motion", a11y.reduced_motion);
+    document.body.classList.toggle("high-contrast", a11y.high_contrast);
+  } catch (err) {
+    console.error("Failed to query accessibility modes", err);
+  }
+
+  try {
+    const accent: string | null = await invoke("get_accent_color");
+    if (accent) document.documentElement.style.setProperty("--dock-accent", accent);
+  } catch (err) {
+    console.error("Failed to query the accent colour", err);
+  }
+
+  try {
+    setDnd(await invoke<boolean>("get_dnd"));
+  } catch (err) {
+    console.error("Failed to query do not disturb", err);
+  }
+
+  try {
+    minimalMode = await invoke("is_minimal");
+    // Minimal mode trades the magnification/bounce for battery life
+    reducedMotion ||= minimalMode;
+    document.body.classList.toggle("reduced-motion", reducedMotion);
+  } catch (err) {
+    console.error("Failed to query minimal mode", err);
+  }
+
+  try {
+    safeMode = await invoke("is_safe_mode");
+    reducedMotion ||= safeMode;
+    document.body.classList.toggle("reduced-motion", reducedMotion);
+  } catch (err) {
+    console.error("Failed to query safe mode", err);
+  }
+
   try {
     const config: Config = await invoke("get_config");
+    showRunningApps = config.show_running_apps ?? false;
+    runningPollMs = config.running_poll_ms;
+    if (config.animation_fps) {
+      frameInterval = 1000 / Math.min(240, Math.max(30, config.animation_fps));
+    }
+    recentSize = config.recently_closed?.size ?? 0;
+    if (config.recently_closed?.expire_minutes !== undefined) {
+      recentExpireMs = config.recently_closed.expire_minutes * 60 * 1000;
+    }
+    launchSound = !!config.launch_sound;
+    launchErrorToast = config.launch_error_toast ?? true;
+    hoverSound = !!config.hover_sound;
+    // Config style plus any runtime override
+    applyStyle(await invoke<DockStyle>("get_style"));
     let isFirstCategory = true;
+    const categories = config.categories.filter((c) => c.enabled !== false);
+    // Only ordering depends on the counts; the dock still renders without them
+    try {
+      launchCounts = await invoke("get_launch_counts");
+    } catch (err) {
+      console.error("Failed to load launch counts", err);
+      launchCounts = {};
+    }
+    try {
+      newItems = new Set(await invoke<string[]>("get_new_items"));
+    } catch (err) {
+      console.error("Failed to load new-item badges", err);
+      newItems = new Set();
+    }
+    categories.forEach((c) => (c.shortcuts = sortShortcuts(c)));
+    const now = new Date();
+    lastSsids = await currentSsids(
+      categories.some((c) => c.shortcuts.some((s) => s.visible_when?.ssid))
+    );
+    const isVisible = (s: Shortcut) => !s.visible_when || conditionsMet(s.visible_when, now, lastSsids);
     let totalItems = 0;
-    config.categories.forEach(c => totalItems += c.shortcuts.length);
+    categories.forEach(c => totalItems += c.collapsed ? 1 : c.shortcuts.filter(isVisible).length);
 
     calculateBaseSize(totalItems);
 
-    for (const category of config.categories) {
+    for (const category of categories) {
       // Add separator between categories
       if (!isFirstCategory) {
         const sep = document.createElement("div");
@@ -142,59 +399,78 @@ async function bootstrap() {
       }
       isFirstCategory = false;
 
+      if (category.collapsed) {
+        const folderEl = createFolderItem(category);
+        dockBarEl.appendChild(folderEl);
+        registerDockItem(folderEl);
+        continue;
+      }
+
       for (const shortcut of category.shortcuts) {
-        const itemEl = document.createElement("div");
-        itemEl.className = "dock-item";
-        itemEl.setAttribute("data-name", shortcut.name);
-        itemEl.setAttribute("data-path", shortcut.path);
-        itemEl.style.width = `${currentBaseSize}px`;
-        itemEl.style.height = `${currentBaseSize}px`;
-
-        // Click to launch with bounce animation
-        const appPath = shortcut.path;
-        itemEl.addEventListener("click", () => {
-          itemEl.classList.add("bouncing");
-          itemEl.addEventListener("animationend", () => {
-            itemEl.classList.remove("bouncing");
-          }, { once: true });
-          invoke("launch_app", { path: appPath }).catch((err: unknown) =>
-            console.error("Launch failed:", err)
-          );
+        const itemEl = createDockItem(shortcut, () => {
+          activateShortcut(shortcut);
+          if (category.sort === "frequency_desc") noteLaunch(category, shortcut);
         });
-
-        const imgEl = document.createElement("img");
-        imgEl.alt = shortcut.name;
-        imgEl.draggable = false;
-        itemEl.appendChild(imgEl);
         dockBarEl.appendChild(itemEl);
-        dockItems.push(itemEl);
-
-        // Initialize scales
-        currentScales.push(1);
-        targetScales.push(1);
-
-        // Async icon loading
-        invoke("get_icon_base64", { path: shortcut.path })
-          .then((base64: unknown) => {
-            if (typeof base64 === "string") {
-              imgEl.src = base64;
-            } else {
-              // SVG placeholder for missing icons
-              imgEl.src = createPlaceholderSVG(shortcut.name);
-            }
-          })
-          .catch(() => {
-            imgEl.src = createPlaceholderSVG(shortcut.name);
-          });
+        if (newItems.has(usageKey(shortcut))) markNew(itemEl, shortcut);
+        if (shortcut.visible_when) {
+          conditionalItems.push({ itemEl, when: shortcut.visible_when });
+        }
+        if (isVisible(shortcut)) {
+          registerDockItem(itemEl);
+        } else {
+          itemEl.classList.add("conditional-hidden");
+        }
       }
     }
 
+    // Nothing to show: explain instead of rendering an empty sliver
+    if (dockItems.length === 0) {
+      dockBarEl.replaceChildren(createEmptyHint());
+    }
+
+    if (config.system_items?.length) {
+      systemStripEl = createSystemStrip(config.system_items);
+      dockBarEl.appendChild(systemStripEl);
+    }
+
+    // Size items for rest (shrunken in compact mode)
+    startAnimation();
+
+    // Clicks outside the bar pass through the window; keep the backend's
+    // idea of where the bar is current. The icons' enlarged hit areas
+    // (style.hit_padding) reach past the bar and must keep their clicks
+    const reportBarRect = () => {
+      if (!dockBarEl) return;
+      let { left, top, right, bottom } = dockBarEl.getBoundingClientRect();
+      for (const hitEl of dockBarEl.querySelectorAll<HTMLElement>(".dock-hit")) {
+        const hit = hitEl.getBoundingClientRect();
+        if (hit.width === 0 && hit.height === 0) continue; // Hidden by visible_when
+        left = Math.min(left, hit.left);
+        top = Math.min(top, hit.top);
+        right = Math.max(right, hit.right);
+        bottom = Math.max(bottom, hit.bottom);
+      }
+      invoke("set_bar_rect", { rect: { left, top, right, bottom } }).catch(console.error);
+    };
+    new ResizeObserver(reportBarRect).observe(dockBarEl);
+    window.addEventListener("resize", reportBarRect);
+
     // ─── Mouse tracking ───
     dockBarEl.addEventListener("mousemove", (e: MouseEvent) => {
+      // Static in safe mode: no magnification
+      if (safeMode || !isWithinBarY(e.clientY)) return;
       mouseX = e.clientX;
+      // A fresh entry restarts the magnification ramp
+      if (!isHovering) hoverStartTime = performance.now();
       isHovering = true;
-      updateTargetScales();
-      startAnimation();
+      queueScaleUpdate();
+    });
+
+    // Right-click on the bar opens settings
+    dockBarEl.addEventListener("contextmenu", (e: MouseEvent) => {
+      e.preventDefault();
+      invoke("open_settings").catch(console.error);
     });
 
     dockBarEl.addEventListener("mouseleave", () => {
@@ -206,8 +482,15 @@ async function bootstrap() {
     // Track mouse even outside dock for smooth exit
     document.addEventListener("mousemove", (e: MouseEvent) => {
       if (!isHovering) return;
+      if (!isWithinBarY(e.clientY)) {
+        // Cursor drifted above the bar: relax back to rest once, then go idle
+        isHovering = false;
+        updateTargetScales();
+        startAnimation();
+        return;
+      }
       mouseX = e.clientX;
-      updateTargetScales();
+      queueScaleUpdate();
     });
 
     // Handle window resize to re-calculate icon sizes
@@ -217,14 +500,910 @@ async function bootstrap() {
       startAnimation();
     });
 
-    // Start polling for running processes
-    startProcessPolling();
+    setupFileDrop();
+
+    setupKeyboardNav();
+
+    // Safe mode shows the icons and nothing more
+    if (!safeMode) {
+      // Start polling for running processes
+      startProcessPolling();
+
+      // Start auto-hide logic
+      setupAutoHide();
 
-    // Start auto-hide logic
-    setupAutoHide();
+      setupIdleTimeouts(config.idle_hide_secs, config.idle_quit_secs);
+    }
+
+    if (conditionalItems.length > 0) {
+      window.setInterval(syncConditionalItems, VISIBILITY_CHECK_INTERVAL);
+    }
   } catch (err) {
     console.error("Failed to load dock configuration", err);
   }
+
+  revealDock();
+}
+
+// ─── Startup fade-in, once the icons are in (or taking too long) ───
+async function revealDock() {
+  const timeout = new Promise((resolve) => window.setTimeout(resolve, REVEAL_TIMEOUT));
+  await Promise.race([Promise.allSettled(iconLoads), timeout]);
+  iconLoads = [];
+  document.body.classList.add("ready");
+  // Backend logs where the startup time went (at debug level)
+  requestAnimationFrame(() => invoke("startup_finished").catch(console.error));
+}
+
+// ─── Config-driven styling ───
+function applyStyle(style: DockStyle) {
+  const root = document.documentElement.style;
+  document.body.dataset.iconShape = style.icon_shape ?? "square";
+  theme = style.theme ?? "dark";
+  document.body.classList.toggle("light", theme === "light");
+  document.body.classList.toggle("labels", style.label_mode === "icons_and_labels");
+
+  // The system UI stack stays behind the custom family, so a font that isn't
+  // installed (or lacks a glyph) degrades instead of rendering boxes
+  if (style.font_family) {
+    const family = style.font_family.replace(/["\\]/g, "");
+    root.setProperty("--dock-label-font", `"${family}", ${SYSTEM_FONT_STACK}`);
+  }
+  if (style.font_size) {
+    const size = Math.min(32, Math.max(8, style.font_size));
+    root.setProperty("--dock-label-size", `${size}px`);
+  }
+  if (style.label_max_width) {
+    root.setProperty("--dock-label-max-width", `${Math.max(40, style.label_max_width)}px`);
+  }
+  tiltEffect = !!style.tilt_effect && !reducedMotion;
+  document.body.classList.toggle("tilt", tiltEffect);
+  document.body.classList.toggle("reflections", !!style.reflections);
+  // Compact mode: rest small, grow to full size while hovered
+  if (style.compact_scale !== undefined) {
+    restScale = Math.min(1, Math.max(0.3, style.compact_scale));
+    document.body.classList.add("compact");
+  }
+  // Tints the blur behind the bar too, since that shows through the colour
+  if (style.background_opacity !== undefined) {
+    const opacity = Math.min(1, Math.max(0, style.background_opacity));
+    root.setProperty("--dock-bg-opacity", `${opacity}`);
+  }
+  if (style.hit_padding !== undefined) {
+    const padding = Math.min(32, Math.max(0, style.hit_padding));
+    root.setProperty("--dock-hit-padding", `${padding}px`);
+  }
+}
+
+// Physical pixels an icon can occupy at full magnification on this display
+function iconPixelSize(): number {
+  return Math.ceil(MAX_BASE_SIZE * MAX_SCALE * window.devicePixelRatio);
+}
+
+// ─── Dock item construction ───
+// `iconSrc` supplies a fixed image (e.g. folders) instead of extracting one
+function createDockItem(
+  shortcut: Shortcut,
+  onActivate: () => void,
+  iconSrc?: string
+): HTMLElement {
+  const itemEl = document.createElement("div");
+  itemEl.className = "dock-item";
+  itemEl.setAttribute("data-name", shortcut.name);
+  itemEl.setAttribute("data-path", shortcut.path);
+  itemEl.setAttribute("data-icon", shortcut.icon ?? shortcut.path);
+  if (shortcut.icon_index !== undefined) {
+    itemEl.setAttribute("data-icon-index", String(shortcut.icon_index));
+  }
+  itemEl.setAttribute("data-match", shortcut.process_match ?? shortcut.path);
+  itemEl.style.width = `${currentBaseSize}px`;
+  itemEl.style.height = `${currentBaseSize}px`;
+  // Focusable so keyboard navigation can put the selection ring on it
+  itemEl.tabIndex = 0;
+
+  // Click to activate with bounce animation
+  itemEl.addEventListener("click", async (e: MouseEvent) => {
+    itemEl.classList.add("ring-pulse");
+    window.setTimeout(() => itemEl.classList.remove("ring-pulse"), RING_PULSE_DURATION);
+    // Alt+click re-extracts the icon instead of activating
+    if (e.altKey && !iconSrc) {
+      refreshIcon(shortcut.icon ?? shortcut.path, shortcut.icon_index);
+      return;
+    }
+    // Declining leaves no trace: no spinner, bounce or launch
+    if (shortcut.confirm) {
+      const confirmed = await invoke<boolean>("confirm_launch", {
+        name: shortcut.name,
+        message: shortcut.confirm,
+      }).catch((err: unknown) => {
+        console.error("Confirmation failed:", err);
+        return false;
+      });
+      if (!confirmed) return;
+    }
+    if (!iconSrc && (shortcut.action?.type ?? "launch") === "launch") {
+      showLaunchSpinner(itemEl);
+    }
+    if (!reducedMotion && !dnd) {
+      itemEl.classList.add("bouncing");
+      itemEl.addEventListener("animationend", () => {
+        itemEl.classList.remove("bouncing");
+      }, { once: true });
+    }
+    onActivate();
+  });
+
+  // Long names are ellipsized; lingering on the icon reveals the full label
+  let fullLabelTimer: number | null = null;
+  itemEl.addEventListener("mouseenter", () => {
+    fullLabelTimer = window.setTimeout(() => itemEl.classList.add("full-label"), FULL_LABEL_DELAY);
+  });
+  itemEl.addEventListener("mouseleave", () => {
+    if (fullLabelTimer) window.clearTimeout(fullLabelTimer);
+    fullLabelTimer = null;
+    itemEl.classList.remove("full-label");
+  });
+
+  // Scroll over a running app's icon to cycle through its windows
+  itemEl.addEventListener("wheel", (e: WheelEvent) => {
+    const match = itemEl.getAttribute("data-match");
+    if (!match || !itemEl.classList.contains("running")) return;
+    e.preventDefault();
+
+    const now = performance.now();
+    if (now - lastWheelCycle < WHEEL_CYCLE_INTERVAL) return;
+    lastWheelCycle = now;
+
+    invoke("cycle_app_windows", { path: match, forward: e.deltaY > 0 }).catch(
+      (err: unknown) => console.error("Window cycling failed:", err)
+    );
+  }, { passive: false });
+
+  if (!iconSrc) {
+    // Right-click on an icon: its own menu instead of the bar's settings
+    itemEl.addEventListener("contextmenu", (e: MouseEvent) => {
+      e.preventDefault();
+      e.stopPropagation();
+      showItemMenu(shortcut.path).catch(console.error);
+    });
+
+    // Pulling an icon up toward the screen drags its target out as a file;
+    // the shell's drag loop swallows the mouse-up, so no click follows
+    itemEl.addEventListener("pointerdown", (e: PointerEvent) => {
+      if (e.button !== 0) return;
+      const startY = e.clientY;
+      const onMove = (move: PointerEvent) => {
+        if (startY - move.clientY < DRAG_OUT_THRESHOLD) return;
+        stop();
+        invoke("drag_shortcut_out", { path: shortcut.path }).catch(
+          (err: unknown) => console.error("Drag out failed:", err)
+        );
+      };
+      const stop = () => {
+        document.removeEventListener("pointermove", onMove);
+        document.removeEventListener("pointerup", stop);
+      };
+      document.addEventListener("pointermove", onMove);
+      document.addEventListener("pointerup", stop);
+    });
+  }
+
+  const imgEl = document.createElement("img");
+  imgEl.alt = shortcut.name;
+  imgEl.draggable = false;
+  itemEl.appendChild(imgEl);
+
+  // Name under the icon, shown in the "icons_and_labels" label mode
+  const labelEl = document.createElement("span");
+  labelEl.className = "dock-label";
+  labelEl.textContent = shortcut.name;
+  itemEl.appendChild(labelEl);
+
+  // UAC shield: clicking will ask for elevation
+  if (shortcut.run_as_admin) {
+    const shieldEl = document.createElement("span");
+    shieldEl.className = "dock-shield";
+    itemEl.appendChild(shieldEl);
+  }
+
+  // Invisible click/hover target reaching past the icon (style.hit_padding)
+  const hitEl = document.createElement("div");
+  hitEl.className = "dock-hit";
+  itemEl.appendChild(hitEl);
+
+  if (iconSrc) {
+    imgEl.src = iconSrc;
+    return itemEl;
+  }
+
+  dropShortcuts.set(itemEl, shortcut);
+
+  // Async icon loading
+  const iconLoad = loadIcon(shortcut.icon ?? shortcut.path, shortcut.icon_index)
+    .then((base64: unknown) => {
+      if (typeof base64 === "string") {
+        imgEl.src = base64;
+      } else {
+        // SVG placeholder for missing icons
+        imgEl.src = createPlaceholderSVG(shortcut.name);
+      }
+    })
+    .catch((err: unknown) => {
+      // Missing targets are common; the reason is only worth a debug line
+      console.debug(err);
+      imgEl.src = createPlaceholderSVG(shortcut.name);
+    });
+  if (!document.body.classList.contains("ready")) iconLoads.push(iconLoad);
+
+  return itemEl;
+}
+
+// ─── Icon loading, shared by items showing the same icon ───
+// Keyed by icon path and index; size and animation are fixed for the page's life
+const iconRequests = new Map<string, Promise<unknown>>();
+const iconUrls = new Map<string, string>();
+
+function iconKey(path: string, iconIndex?: number): string {
+  return `${path.toLowerCase()}|${iconIndex ?? ""}`;
+}
+
+// Different targets can extract to identical pixels (a .lnk and its .exe,
+// many shortcuts into one app folder); keep a single copy of each data URL
+function internIconUrl(url: unknown): unknown {
+  if (typeof url !== "string") return url;
+  const existing = iconUrls.get(url);
+  if (existing) return existing;
+  iconUrls.set(url, url);
+  return url;
+}
+
+function loadIcon(path: string, iconIndex?: number): Promise<unknown> {
+  const key = iconKey(path, iconIndex);
+  let request = iconRequests.get(key);
+  if (!request) {
+    request = invoke("get_icon_base64", {
+      path,
+      size: iconPixelSize(),
+      iconIndex,
+      animate: !reducedMotion,
+    })
+      .then(internIconUrl)
+      .catch((err) => {
+        // Don't cache the failure; the next rebuild asks again
+        if (iconRequests.get(key) === request) iconRequests.delete(key);
+        throw err;
+      });
+    iconRequests.set(key, request);
+  }
+  return request;
+}
+
+// ─── "New" badge until a fresh shortcut's first click ───
+function markNew(itemEl: HTMLElement, shortcut: Shortcut) {
+  itemEl.classList.add("new");
+  const badgeEl = document.createElement("span");
+  badgeEl.className = "dock-new";
+  itemEl.appendChild(badgeEl);
+  itemEl.addEventListener("click", () => {
+    itemEl.classList.remove("new");
+    badgeEl.remove();
+    const key = usageKey(shortcut);
+    newItems.delete(key);
+    invoke("mark_seen", { key }).catch(console.error);
+  }, { once: true });
+}
+
+// ─── Icon context menu ───
+async function showItemMenu(path: string) {
+  const menu = await Menu.new({
+    items: [
+      {
+        id: "copy-path",
+        text: "Copy path",
+        action: () => {
+          invoke("copy_shortcut_path", { path }).catch(console.error);
+        },
+      },
+      {
+        id: "settings",
+        text: "Settings…",
+        action: () => {
+          invoke("open_settings").catch(console.error);
+        },
+      },
+    ],
+  });
+  await menu.popup();
+}
+
+// ─── Launch feedback: spinner until the app shows up as running ───
+function showLaunchSpinner(itemEl: HTMLElement) {
+  // Already running: single-instance focus or a second window, no wait
+  if (itemEl.classList.contains("running")) return;
+  itemEl.classList.add("launching");
+  window.setTimeout(() => itemEl.classList.remove("launching"), LAUNCH_SPINNER_TIMEOUT);
+}
+
+// ─── Launch failures (backend "launch-failed") ───
+function showLaunchFailure(path: string, error: string) {
+  const target = path.toLowerCase();
+  for (const item of dockBarEl?.querySelectorAll<HTMLElement>(".dock-item") ?? []) {
+    if (item.getAttribute("data-path")?.toLowerCase() !== target) continue;
+    item.classList.remove("launching", "launch-failed");
+    // Restart the shake if it's still running from a previous failure
+    void item.offsetWidth;
+    item.classList.add("launch-failed");
+    window.setTimeout(() => item.classList.remove("launch-failed"), LAUNCH_FAILED_DURATION);
+  }
+  if (launchErrorToast) showToast(error);
+}
+
+let toastEl: HTMLElement | null = null;
+let toastTimer: number | null = null;
+
+// Short message over the bar; a newer one replaces it
+function showToast(message: string) {
+  if (!toastEl) {
+    toastEl = document.createElement("div");
+    toastEl.className = "dock-toast";
+    toastEl.setAttribute("role", "alert");
+    document.body.appendChild(toastEl);
+  }
+  toastEl.textContent = message;
+  toastEl.classList.add("visible");
+  if (toastTimer) window.clearTimeout(toastTimer);
+  toastTimer = window.setTimeout(() => toastEl?.classList.remove("visible"), TOAST_DURATION);
+}
+
+// ─── Drawing attention to an icon (backend `highlight_icon`) ───
+function highlightItems(path: string, durationMs: number) {
+  if (dnd) return;
+  const target = path.toLowerCase();
+  for (const item of dockItems) {
+    if (item.getAttribute("data-path")?.toLowerCase() !== target) continue;
+    item.classList.add("highlighted");
+    window.setTimeout(() => item.classList.remove("highlighted"), durationMs);
+  }
+}
+
+// ─── Do not disturb (backend `set_dnd`) ───
+function setDnd(enabled: boolean) {
+  dnd = enabled;
+  document.body.classList.toggle("dnd", enabled);
+  if (enabled) {
+    for (const item of dockItems) item.classList.remove("highlighted", "bouncing");
+  }
+}
+
+// ─── Keyboard navigation: arrows move the selection ring, Enter/Space activate ───
+function setupKeyboardNav() {
+  document.addEventListener("keydown", (e: KeyboardEvent) => {
+    if (!dockBarEl) return;
+    // DOM order: expanded folder items sit next to their folder
+    const items = Array.from(dockBarEl.querySelectorAll<HTMLElement>(".dock-item:not(.conditional-hidden)"));
+    const index = items.indexOf(document.activeElement as HTMLElement);
+
+    if (e.key === "ArrowRight" || e.key === "ArrowLeft") {
+      e.preventDefault();
+      if (items.length === 0) return;
+      const step = e.key === "ArrowRight" ? 1 : -1;
+      const next = index === -1 ? 0 : (index + step + items.length) % items.length;
+      items[next].focus();
+    } else if ((e.key === "Enter" || e.key === " ") && index !== -1) {
+      e.preventDefault();
+      items[index].click();
+    }
+  });
+}
+
+// ─── Dropping files onto an icon opens them with that app ───
+function acceptsDrop(itemEl: HTMLElement): boolean {
+  const shortcut = dropShortcuts.get(itemEl);
+  return !!shortcut && (shortcut.action?.type ?? "launch") === "launch";
+}
+
+function setDropTarget(itemEl: HTMLElement | null) {
+  if (itemEl === dropTargetEl) return;
+  dropTargetEl?.classList.remove("drop-target", "drop-rejected");
+  dropTargetEl = itemEl;
+  itemEl?.classList.add(acceptsDrop(itemEl) ? "drop-target" : "drop-rejected");
+}
+
+function setupFileDrop() {
+  getCurrentWebview()
+    .onDragDropEvent((event) => {
+      const drag = event.payload;
+      if (drag.type === "leave") {
+        setDropTarget(null);
+        return;
+      }
+
+      // OS drags don't raise mouseenter, so reveal an auto-hidden dock here
+      if (drag.type === "enter" && isHidden) {
+        isHidden = false;
+        invoke("set_dock_hidden", { hidden: false }).catch(console.error);
+      }
+
+      // Drag positions are physical pixels relative to the webview
+      const hit = document.elementFromPoint(
+        drag.position.x / window.devicePixelRatio,
+        drag.position.y / window.devicePixelRatio
+      );
+      const itemEl = hit?.closest<HTMLElement>(".dock-item") ?? null;
+      if (drag.type !== "drop") {
+        setDropTarget(itemEl);
+        return;
+      }
+
+      setDropTarget(null);
+      if (!itemEl || !acceptsDrop(itemEl) || drag.paths.length === 0) return;
+      const shortcut = dropShortcuts.get(itemEl)!;
+      showLaunchSpinner(itemEl);
+      invoke("open_with", { path: shortcut.path, files: drag.paths }).catch(
+        (err: unknown) => console.error("Open with failed:", err)
+      );
+    })
+    .catch(console.error);
+}
+
+// ─── Empty dock ───
+function createEmptyHint(): HTMLElement {
+  const hintEl = document.createElement("div");
+  hintEl.className = "dock-empty-hint";
+  hintEl.textContent = "No shortcuts configured — click to open settings";
+  hintEl.addEventListener("click", () => {
+    invoke("open_settings").catch(console.error);
+  });
+  return hintEl;
+}
+
+// ─── Collapsed categories: one folder icon that expands in place ───
+function createFolderItem(category: Category): HTMLElement {
+  let expanded: HTMLElement[] = [];
+
+  const folderEl = createDockItem({ name: category.name, path: "" }, () => {
+    if (!dockBarEl) return;
+
+    if (expanded.length > 0) {
+      expanded.forEach((itemEl) => removeDockItem(itemEl));
+      expanded = [];
+    } else {
+      let anchor: Element = folderEl;
+      const now = new Date();
+      for (const shortcut of category.shortcuts) {
+        if (shortcut.visible_when && !conditionsMet(shortcut.visible_when, now, lastSsids)) continue;
+        const itemEl = createDockItem(shortcut, () => activateShortcut(shortcut));
+        anchor.after(itemEl);
+        anchor = itemEl;
+        registerDockItem(itemEl);
+        revealDockItem(itemEl);
+        expanded.push(itemEl);
+      }
+    }
+
+    folderEl.classList.toggle("expanded", expanded.length > 0);
+    calculateBaseSize(dockItems.length);
+    updateTargetScales();
+    startAnimation();
+  }, createFolderSVG());
+
+  folderEl.classList.add("folder");
+  return folderEl;
+}
+
+// ─── Feedback sounds (played by the backend) ───
+function playSound(kind: "launch" | "hover") {
+  invoke("play_sound", { kind }).catch(console.error);
+}
+
+// ─── Shortcut ordering ───
+// Mirrors `usage::key` on the backend
+function usageKey(shortcut: Shortcut): string {
+  return (shortcut.path || shortcut.name).toLowerCase();
+}
+
+// Display order for a category; Array.sort is stable, so ties keep file order
+function sortShortcuts(category: Category): Shortcut[] {
+  const shortcuts = [...category.shortcuts];
+  switch (category.sort ?? "manual") {
+    case "name_asc":
+      return shortcuts.sort((a, b) => a.name.localeCompare(b.name));
+    case "name_desc":
+      return shortcuts.sort((a, b) => b.name.localeCompare(a.name));
+    case "frequency_desc":
+      return shortcuts.sort(
+        (a, b) => (launchCounts[usageKey(b)] ?? 0) - (launchCounts[usageKey(a)] ?? 0)
+      );
+    default:
+      return shortcuts;
+  }
+}
+
+// A launch in a frequency-sorted category may reorder it: rebuild once the
+// bounce has played
+function noteLaunch(category: Category, shortcut: Shortcut) {
+  if (shortcut.action?.type === "builtin") return;
+  const key = usageKey(shortcut);
+  launchCounts[key] = (launchCounts[key] ?? 0) + 1;
+  const resorted = sortShortcuts(category);
+  if (resorted.some((s, i) => s !== category.shortcuts[i])) {
+    window.setTimeout(() => window.location.reload(), RESORT_DELAY);
+  }
+}
+
+// ─── Click actions ───
+function activateShortcut(shortcut: Shortcut) {
+  if (launchSound) playSound("launch");
+  const action: ShortcutAction = shortcut.action ?? { type: "launch" };
+  if (action.type === "builtin") {
+    runBuiltin(action.name);
+    return;
+  }
+  invoke("run_action", { shortcut }).catch((err: unknown) =>
+    console.error("Action failed:", err)
+  );
+}
+
+// Dock-internal actions that only make sense on the UI side
+function runBuiltin(name: string) {
+  switch (name) {
+    case "hide_dock":
+      isHidden = true;
+      invoke("set_dock_hidden", { hidden: true }).catch(console.error);
+      break;
+    case "open_settings":
+      invoke("open_settings").catch(console.error);
+      break;
+    case "reload":
+      window.location.reload();
+      break;
+    default:
+      console.warn(`Unknown builtin action "${name}", ignoring`);
+  }
+}
+
+// Keep dockItems and the scale arrays index-aligned
+function registerDockItem(itemEl: HTMLElement) {
+  // First item (e.g. a running app) replaces the empty-dock hint
+  dockBarEl?.querySelector(".dock-empty-hint")?.remove();
+  dockItems.push(itemEl);
+  currentScales.push(restScale);
+  targetScales.push(restScale);
+}
+
+// Items added after startup grow into their slot (widening the bar smoothly
+// via the width transition) while the icon fades and scales in
+function revealDockItem(itemEl: HTMLElement) {
+  if (reducedMotion) return;
+
+  itemEl.style.width = "0px";
+  itemEl.classList.add("entering");
+  itemEl.addEventListener("animationend", () => {
+    itemEl.classList.remove("entering");
+  }, { once: true });
+  // Commit the zero width before restoring it, so the change transitions
+  void itemEl.offsetWidth;
+  itemEl.style.width = `${currentBaseSize}px`;
+}
+
+// `onGone` runs once the item has animated out; by default it is discarded
+function removeDockItem(itemEl: HTMLElement, onGone = () => itemEl.remove()) {
+  const index = dockItems.indexOf(itemEl);
+  if (index !== -1) {
+    dockItems.splice(index, 1);
+    currentScales.splice(index, 1);
+    targetScales.splice(index, 1);
+  }
+
+  if (reducedMotion) {
+    onGone();
+    return;
+  }
+
+  // "Poof": the icon puffs away in its slot first, then the gap closes
+  itemEl.classList.add("poofing");
+  itemEl.addEventListener("animationend", () => {
+    itemEl.style.width = "0px";
+    itemEl.addEventListener("transitionend", onGone, { once: true });
+  }, { once: true });
+}
+
+// ─── Per-shortcut visibility conditions (visible_when) ───
+const DAY_NAMES = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+// "9-17" -> [9, 17]; end exclusive, and start > end wraps past midnight
+function parseHours(spec: string): [number, number] | null {
+  const match = /^\s*(\d{1,2})\s*-\s*(\d{1,2})\s*$/.exec(spec);
+  if (!match) return null;
+  const [start, end] = [Number(match[1]), Number(match[2])];
+  return start <= 24 && end <= 24 ? [start, end] : null;
+}
+
+// "mon-fri", "sat,sun", "fri-mon" -> day numbers as in Date.getDay()
+function parseDays(spec: string): Set<number> | null {
+  const days = new Set<number>();
+  for (const part of spec.toLowerCase().split(",")) {
+    const ends = part.split("-").map((d) => DAY_NAMES.indexOf(d.trim().slice(0, 3)));
+    const [from, to = from] = ends;
+    if (ends.length > 2 || from === -1 || to === -1) return null;
+    for (let day = from; ; day = (day + 1) % 7) {
+      days.add(day);
+      if (day === to) break;
+    }
+  }
+  return days;
+}
+
+// A condition that doesn't parse is ignored rather than hiding the icon
+function conditionsMet(when: VisibleWhen, now: Date, ssids: string[]): boolean {
+  if (when.hours) {
+    const range = parseHours(when.hours);
+    if (range) {
+      const [start, end] = range;
+      const hour = now.getHours();
+      const inRange = start <= end ? hour >= start && hour < end : hour >= start || hour < end;
+      if (!inRange) return false;
+    } else {
+      console.warn(`Ignoring unparseable visible_when.hours "${when.hours}"`);
+    }
+  }
+  if (when.days) {
+    const days = parseDays(when.days);
+    if (days) {
+      if (!days.has(now.getDay())) return false;
+    } else {
+      console.warn(`Ignoring unparseable visible_when.days "${when.days}"`);
+    }
+  }
+  // SSIDs are case-sensitive
+  if (when.ssid !== undefined && !ssids.includes(when.ssid)) return false;
+  return true;
+}
+
+async function currentSsids(needed: boolean): Promise<string[]> {
+  if (!needed) return [];
+  try {
+    return await invoke<string[]>("get_wifi_ssids");
+  } catch (err) {
+    console.error("Failed to query Wi-Fi networks", err);
+    return [];
+  }
+}
+
+// Animate icons out/in as their conditions stop/start holding
+async function syncConditionalItems() {
+  const now = new Date();
+  lastSsids = await currentSsids(conditionalItems.some((c) => c.when.ssid !== undefined));
+  let changed = false;
+
+  for (const { itemEl, when } of conditionalItems) {
+    const shown = !itemEl.classList.contains("conditional-hidden");
+    if (conditionsMet(when, now, lastSsids) === shown) continue;
+    changed = true;
+    if (shown) {
+      // Kept for when the conditions hold again
+      removeDockItem(itemEl, () => {
+        itemEl.classList.remove("poofing");
+        itemEl.classList.add("conditional-hidden");
+      });
+    } else {
+      itemEl.classList.remove("conditional-hidden");
+      registerDockItem(itemEl);
+      revealDockItem(itemEl);
+    }
+  }
+
+  if (changed) {
+    calculateBaseSize(dockItems.length);
+    updateTargetScales();
+    startAnimation();
+  }
+}
+
+// ─── Running-but-unpinned apps, shown after the pinned set while open ───
+function appNameFromPath(path: string): string {
+  const file = path.split(/[\\/]/).pop() ?? path;
+  return file.replace(/\.exe$/i, "");
+}
+
+function syncTransientItems(windowedPaths: string[]) {
+  if (!dockBarEl) return;
+
+  const isTransient = (item: HTMLElement) => item.classList.contains("transient");
+  const pathOf = (item: HTMLElement) => item.getAttribute("data-path")?.toLowerCase() ?? "";
+
+  const pinned = dockItems.filter((i) => !isTransient(i));
+  const wanted = new Set(
+    windowedPaths.filter((p) => !pinned.some((item) => isRunningItem(item, [p])))
+  );
+  let changed = false;
+
+  // Drop icons whose app has exited
+  for (const item of dockItems.filter(isTransient)) {
+    if (!wanted.has(pathOf(item))) {
+      removeDockItem(item);
+      changed = true;
+    }
+  }
+
+  const present = new Set(dockItems.filter(isTransient).map(pathOf));
+  for (const path of wanted) {
+    if (present.has(path)) continue;
+    const itemEl = createDockItem({ name: appNameFromPath(path), path }, () => {
+      invoke("focus_app", { path }).catch((err: unknown) =>
+        console.error("Focus failed:", err)
+      );
+    });
+    itemEl.classList.add("transient");
+    dockBarEl.insertBefore(itemEl, systemStripEl);
+    registerDockItem(itemEl);
+    revealDockItem(itemEl);
+    changed = true;
+  }
+
+  const firstTransient = dockItems.find(isTransient);
+  if (firstTransient && !transientSepEl) {
+    transientSepEl = document.createElement("div");
+    transientSepEl.className = "dock-separator";
+    dockBarEl.insertBefore(transientSepEl, firstTransient);
+  } else if (!firstTransient && transientSepEl) {
+    transientSepEl.remove();
+    transientSepEl = null;
+  }
+
+  if (changed) {
+    calculateBaseSize(dockItems.length);
+    updateTargetScales();
+    startAnimation();
+  }
+}
+
+// ─── Recently closed pinned apps, a transient section for quick relaunch ───
+function syncRecentItems(closedItems: HTMLElement[]) {
+  if (!dockBarEl) return;
+  const now = Date.now();
+  let changed = false;
+
+  const forget = (key: string) => {
+    const entry = recentlyClosed.get(key);
+    if (!entry) return;
+    recentlyClosed.delete(key);
+    removeDockItem(entry.itemEl);
+    changed = true;
+  };
+
+  // Expired, or running again (relaunched from anywhere)
+  for (const [key, entry] of recentlyClosed) {
+    if (now - entry.closedAt > recentExpireMs || entry.itemEl.classList.contains("running")) {
+      forget(key);
+    }
+  }
+
+  for (const closedEl of closedItems) {
+    const isSection = closedEl.classList.contains("transient") || closedEl.classList.contains("recent");
+    const shortcut = dropShortcuts.get(closedEl);
+    if (isSection || !shortcut) continue;
+
+    const key = usageKey(shortcut);
+    forget(key);
+    const itemEl = createDockItem(shortcut, () => {
+      activateShortcut(shortcut);
+      forget(key);
+    });
+    itemEl.classList.add("recent");
+    // Ahead of the running-apps section and utility strip, if there are any
+    dockBarEl.insertBefore(itemEl, transientSepEl ?? systemStripEl);
+    registerDockItem(itemEl);
+    revealDockItem(itemEl);
+    recentlyClosed.set(key, { shortcut, closedAt: now, itemEl });
+    changed = true;
+  }
+
+  // Oldest drop off first
+  for (const key of Array.from(recentlyClosed.keys()).slice(0, -recentSize)) {
+    forget(key);
+  }
+
+  const firstRecent = recentlyClosed.values().next().value?.itemEl;
+  if (firstRecent && !recentSepEl) {
+    recentSepEl = document.createElement("div");
+    recentSepEl.className = "dock-separator";
+    dockBarEl.insertBefore(recentSepEl, firstRecent);
+  } else if (!firstRecent && recentSepEl) {
+    recentSepEl.remove();
+    recentSepEl = null;
+  }
+
+  if (changed) {
+    calculateBaseSize(dockItems.length);
+    updateTargetScales();
+    startAnimation();
+  }
+}
+
+// ─── Utility strip (config: system_items), pinned after everything else ───
+// Not registered in dockItems: fixed size, no magnification or reordering
+function createSystemStrip(items: SystemItem[]): HTMLElement {
+  const stripEl = document.createElement("div");
+  stripEl.className = "dock-system";
+  const sep = document.createElement("div");
+  sep.className = "dock-separator";
+  stripEl.appendChild(sep);
+
+  for (const item of items) {
+    const itemEl = createSystemItem(item);
+    if (!itemEl) continue;
+    itemEl.classList.add("system");
+    // Sized by --dock-icon-size rather than the per-item magnification
+    itemEl.style.width = "";
+    itemEl.style.height = "";
+    stripEl.appendChild(itemEl);
+  }
+  return stripEl;
+}
+
+function createSystemItem(item: SystemItem): HTMLElement | null {
+  switch (item.type) {
+    case "settings":
+      return createDockItem({ name: "Settings", path: "" }, () => {
+        invoke("open_settings").catch(console.error);
+      }, createGlyphSVG("\u2699"));
+    case "toggle_theme":
+      return createDockItem({ name: "Toggle theme", path: "" }, () => {
+        const partial = { theme: theme === "dark" ? "light" : "dark" };
+        invoke("set_style_override", { partial }).catch(console.error);
+      }, createGlyphSVG("\u25D0"));
+    case "clock":
+      return createClockItem();
+    default:
+      console.warn(`Unknown system item "${item.type}", ignoring`);
+      return null;
+  }
+}
+
+// Time as the icon, the date as its tooltip; redrawn on the minute
+function createClockItem(): HTMLElement {
+  const itemEl = createDockItem({ name: "", path: "" }, () => {}, createGlyphSVG(""));
+  const imgEl = itemEl.querySelector("img");
+  const labelEl = itemEl.querySelector(".dock-label");
+  const tick = () => {
+    const now = new Date();
+    const date = now.toLocaleDateString(undefined, { weekday: "long", month: "long", day: "numeric" });
+    const time = now.toLocaleTimeString(undefined, { hour: "2-digit", minute: "2-digit" });
+    itemEl.setAttribute("data-name", date);
+    if (labelEl) labelEl.textContent = date;
+    if (imgEl) imgEl.src = createGlyphSVG(time, 18);
+    window.setTimeout(tick, 60_000 - (now.getTime() % 60_000));
+  };
+  tick();
+  return itemEl;
+}
+
+// A tile with centred text, for items without app artwork
+function createGlyphSVG(text: string, fontSize = 34): string {
+  const svg = `<svg xmlns="http://www.w3.org/2000/svg" width="64" height="64" viewBox="0 0 64 64">
+    <rect width="64" height="64" rx="14" fill="#3A3A3C"/>
+    <text x="32" y="32" dominant-baseline="central" text-anchor="middle" fill="white" font-size="${fontSize}" font-weight="600" font-family="Inter, sans-serif">${text}</text>
+  </svg>`;
+
+  // Not base64: btoa() can't take the non-Latin-1 glyphs
+  return `data:image/svg+xml;charset=utf-8,${encodeURIComponent(svg)}`;
+}
+
+// ─── Folder icon for collapsed categories ───
+function createFolderSVG(): string {
+  const svg = `<svg xmlns="http://www.w3.org/2000/svg" width="64" height="64" viewBox="0 0 64 64">
+    <defs>
+      <linearGradient id="f" x1="0" y1="0" x2="0" y2="1">
+        <stop offset="0%" stop-color="#6FB6F5"/>
+        <stop offset="100%" stop-color="#3C8DDB"/>
+      </linearGradient>
+    </defs>
+    <path d="M6 16a4 4 0 0 1 4-4h14l6 6h24a4 4 0 0 1 4 4v28a4 4 0 0 1-4 4H10a4 4 0 0 1-4-4z" fill="#2F7BC4"/>
+    <rect x="6" y="22" width="52" height="32" rx="4" fill="url(#f)"/>
+  </svg>`;
+
+  return `data:image/svg+xml;base64,${btoa(svg)}`;
 }
 
 // ─── Placeholder icon for missing executables ───
@@ -247,29 +1426,137 @@ function createPlaceholderSVG(name: string): string {
   return `data:image/svg+xml;base64,${btoa(svg)}`;
 }
 
+// ─── Re-fetch a single icon (e.g. after an app auto-updated) ───
+async function refreshIcon(path: string, iconIndex?: number) {
+  if (!dockBarEl) return;
+  // Includes icons currently hidden by visible_when; matched the way
+  // iconRequests caches them, so paths differing only in case count too
+  const key = iconKey(path, iconIndex);
+  const items = Array.from(dockBarEl.querySelectorAll<HTMLElement>(".dock-item"))
+    .filter((item) => {
+      const index = item.getAttribute("data-icon-index");
+      return iconKey(item.getAttribute("data-icon") ?? "", index === null ? undefined : Number(index)) === key;
+    });
+  if (items.length === 0) return;
+
+  let base64: unknown = null;
+  try {
+    base64 = internIconUrl(await invoke("refresh_icon", {
+      path,
+      size: iconPixelSize(),
+      iconIndex,
+      animate: !reducedMotion,
+    }));
+    iconRequests.set(key, Promise.resolve(base64));
+  } catch (err) {
+    // e.g. the target was uninstalled: show that rather than the stale icon
+    console.error("Icon refresh failed:", err);
+  }
+  for (const item of items) {
+    const imgEl = item.querySelector("img");
+    if (!imgEl) continue;
+    imgEl.src = typeof base64 === "string"
+      ? base64
+      : createPlaceholderSVG(item.getAttribute("data-name") ?? "");
+  }
+}
+
+// ─── Re-extract every icon in place (backend `reload_icons`) ───
+async function reloadAllIcons() {
+  if (!dockBarEl) return;
+  iconRequests.clear();
+  iconUrls.clear();
+
+  const targets = new Map<string, Shortcut>();
+  for (const item of dockBarEl.querySelectorAll<HTMLElement>(".dock-item")) {
+    const shortcut = dropShortcuts.get(item);
+    if (!shortcut) continue; // folders and utility items have fixed artwork
+    targets.set(iconKey(shortcut.icon ?? shortcut.path, shortcut.icon_index), shortcut);
+  }
+  await Promise.all(
+    Array.from(targets.values(), (s) => refreshIcon(s.icon ?? s.path, s.icon_index))
+  );
+}
+
+// A bare image name ("app.exe") matches by file name, anything with a
+// separator must equal the full image path. Case-insensitive either way.
+function matchesProcess(pattern: string, imagePath: string): boolean {
+  const p = pattern.toLowerCase();
+  const image = imagePath.toLowerCase();
+  if (/[\\/]/.test(p)) return p === image;
+  return (image.split(/[\\/]/).pop() ?? image) === p;
+}
+
+function isRunningItem(item: HTMLElement, runningPaths: string[]): boolean {
+  const match = item.getAttribute("data-match");
+  return !!match && runningPaths.some((path) => matchesProcess(match, path));
+}
+
+function windowCountOf(item: HTMLElement, counts: Record<string, number>): number {
+  const match = item.getAttribute("data-match");
+  if (!match) return 0;
+  let total = 0;
+  for (const [path, count] of Object.entries(counts)) {
+    if (matchesProcess(match, path)) total += count;
+  }
+  return total;
+}
+
+// Running indicator: one dot per window, capped with a "more" marker
+function setWindowDots(item: HTMLElement, count: number) {
+  let dotsEl = item.querySelector<HTMLElement>(".dock-dots");
+  if (count === 0) {
+    dotsEl?.remove();
+    return;
+  }
+  if (!dotsEl) {
+    dotsEl = document.createElement("span");
+    dotsEl.className = "dock-dots";
+    item.appendChild(dotsEl);
+  }
+  const shown = Math.min(count, MAX_WINDOW_DOTS);
+  if (dotsEl.childElementCount !== shown) {
+    dotsEl.replaceChildren(...Array.from({ length: shown }, () => document.createElement("i")));
+  }
+  dotsEl.classList.toggle("more", count > MAX_WINDOW_DOTS);
+}
+
 async function startProcessPolling() {
-  const poll = async () => {
+  const poll = async (force = false) => {
     try {
-      const runningPaths: string[] = await invoke("get_running_apps");
-      const runningSet = new Set(runningPaths.map((p) => p.toLowerCase()));
+      if (showRunningApps) {
+        const windowedPaths: string[] = await invoke("get_windowed_apps");
+        syncTransientItems(windowedPaths);
+      }
 
+      const runningPaths: string[] = await invoke("get_running_apps", { force });
+      const windowCounts: Record<string, number> = await invoke("get_window_counts");
+
+      const closed: HTMLElement[] = [];
       dockItems.forEach((item) => {
-        const path = item.getAttribute("data-path")?.toLowerCase();
-        if (path && runningSet.has(path)) {
-          item.classList.add("running");
-        } else {
-          item.classList.remove("running");
-        }
+        const running = isRunningItem(item, runningPaths);
+        if (!running && item.classList.contains("running")) closed.push(item);
+        item.classList.toggle("running", running);
+        if (running) item.classList.remove("launching");
+        // A running app without windows (e.g. in the tray) still gets a dot
+        setWindowDots(item, running ? Math.max(1, windowCountOf(item, windowCounts)) : 0);
       });
+      if (recentSize > 0) syncRecentItems(closed);
     } catch (err) {
       console.error("Process polling failed:", err);
     }
   };
 
-  // Initial check
-  poll();
-  // Periodic poll
-  setInterval(poll, 2500);
+  // Initial check, never served from the backend's cache
+  poll(true);
+  // Periodic poll, relaxed in minimal mode unless configured; nothing to
+  // update while the dock is covered, so catch up once it's visible again
+  setInterval(() => {
+    if (!occluded) poll();
+  }, Math.max(MIN_RUNNING_POLL_MS, runningPollMs ?? (minimalMode ? 10000 : 2500)));
+  listen<boolean>("dock-occluded", (event) => {
+    if (!event.payload) poll();
+  }).catch(console.error);
 }
 
 function setupAutoHide() {
@@ -298,6 +1585,100 @@ function setupAutoHide() {
   });
 }
 
+// ─── Kiosk idle timeouts: hide and/or quit after no interaction ───
+function setupIdleTimeouts(hideSecs?: number, quitSecs?: number) {
+  // 0 counts as unset: a hand-edited config skips validation, and it would
+  // fire on the first mouse move
+  if (!hideSecs && !quitSecs) return;
+  let hideTimer: number | null = null;
+  let quitTimer: number | null = null;
+
+  const restart = () => {
+    if (hideTimer) clearTimeout(hideTimer);
+    if (quitTimer) clearTimeout(quitTimer);
+    if (hideSecs) {
+      hideTimer = window.setTimeout(() => {
+        if (isHidden) return;
+        isHidden = true;
+        invoke("set_dock_hidden", { hidden: true }).catch(console.error);
+      }, hideSecs * 1000);
+    }
+    if (quitSecs) {
+      // The backend restores the taskbar and drops the AppBar on the way out
+      quitTimer = window.setTimeout(() => {
+        invoke("quit_dock").catch(console.error);
+      }, quitSecs * 1000);
+    }
+  };
+
+  for (const type of ["mousemove", "mouseup", "keydown", "wheel"]) {
+    document.addEventListener(type, restart, { passive: true });
+  }
+  restart();
+}
+
 window.addEventListener("DOMContentLoaded", () => {
   bootstrap();
 });
+
+// Settings were saved: rebuild the dock from the fresh config
+listen("config-changed", () => {
+  window.location.reload();
+}).catch(console.error);
+
+// Labels toggled at runtime: animate them in/out rather than rebuilding
+listen<LabelMode>("label-mode-changed", (event) => {
+  document.body.classList.toggle("labels", event.payload === "icons_and_labels");
+}).catch(console.error);
+
+// Icons re-extracted in place; layout and order are kept
+listen("reload-icons", () => {
+  reloadAllIcons().catch(console.error);
+}).catch(console.error);
+
+// A runtime style override was set or cleared
+listen("style-changed", () => {
+  window.location.reload();
+}).catch(console.error);
+
+// Switched between AC and battery (or the mode changed): rebuild with the new
+// animation/polling settings
+listen("minimal-changed", () => {
+  window.location.reload();
+}).catch(console.error);
+
+listen<{ path: string; duration_ms: number }>("highlight-icon", (event) => {
+  highlightItems(event.payload.path, event.payload.duration_ms);
+}).catch(console.error);
+
+listen<boolean>("dnd-changed", (event) => {
+  setDnd(event.payload);
+}).catch(console.error);
+
+// The toggle-hidden hotkey moved the dock behind our back
+listen<boolean>("dock-hidden-changed", (event) => {
+  isHidden = event.payload;
+}).catch(console.error);
+
+listen<{ path: string; error: string }>("launch-failed", (event) => {
+  showLaunchFailure(event.payload.path, event.payload.error);
+}).catch(console.error);
+
+// Fully covered by another window: stop animating until it's visible again
+listen<boolean>("dock-occluded", (event) => {
+  occluded = event.payload;
+  if (occluded) {
+    if (animFrameId !== null) cancelAnimationFrame(animFrameId);
+    animFrameId = null;
+    isHovering = false;
+  } else {
+    updateTargetScales();
+    startAnimation();
+  }
+}).catch(console.error);
+
+// Moved to a monitor with different scaling: icons were extracted for the old
+// pixel density, so rebuild the dock to fetch them at the new one
+window
+  .matchMedia(`(resolution: ${window.devicePixelRatio}dppx)`)
+  .addEventListener("change", () => window.location.reload(), { once: true });
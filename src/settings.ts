@@ -0,0 +1,271 @@
+import { invoke } from "@tauri-apps/api/core";
+
+// ═══════════════════════════════════════════════════════════
+// OxideDock — Settings window (edit categories & shortcuts)
+// ═══════════════════════════════════════════════════════════
+
+interface Shortcut {
+  name: string;
+  path: string;
+  action?: { type: string };
+  discovered?: boolean;
+}
+
+// Command/builtin/group actions don't use `path`, so an empty one is fine there
+function needsPath(shortcut: Shortcut): boolean {
+  const type = shortcut.action?.type ?? "launch";
+  return type !== "command" && type !== "builtin" && type !== "launch_group";
+}
+
+interface Category {
+  name: string;
+  shortcuts: Shortcut[];
+  source?: string;
+  discovered?: boolean;
+}
+
+// Only the categories (and discovery.hidden) are edited here; other fields
+// round-trip untouched
+interface Config {
+  categories: Category[];
+  discovery?: { hidden?: string[]; [key: string]: unknown };
+  [key: string]: unknown;
+}
+
+interface ConfigInfo {
+  path: string;
+  exists: boolean;
+  categories: number;
+  shortcuts: number;
+  error: string | null;
+}
+
+let config: Config | null = null;
+let categoriesEl: HTMLElement | null = null;
+let statusEl: HTMLElement | null = null;
+
+function setStatus(message: string, isError = false) {
+  if (!statusEl) return;
+  statusEl.textContent = message;
+  statusEl.classList.toggle("error", isError);
+}
+
+// ─── Which file is in use, and did it load ───
+async function showConfigInfo() {
+  const infoEl = document.getElementById("config-info");
+  if (!infoEl) return;
+
+  try {
+    const info: ConfigInfo = await invoke("config_info");
+    if (info.error) {
+      infoEl.textContent = `${info.path} — failed to load: ${info.error}`;
+    } else if (!info.exists) {
+      infoEl.textContent = `${info.path} — not found, saving will create it`;
+    } else {
+      infoEl.textContent =
+        `${info.path} — ${info.categories} categories, ${info.shortcuts} shortcuts`;
+    }
+    infoEl.classList.toggle("error", !!info.error);
+  } catch (err) {
+    infoEl.textContent = `Config info unavailable: ${err}`;
+  }
+}
+
+// ─── Small DOM helpers ───
+function textInput(
+  className: string,
+  value: string,
+  placeholder: string,
+  onChange: (value: string) => void
+): HTMLInputElement {
+  const input = document.createElement("input");
+  input.type = "text";
+  input.className = className;
+  input.value = value;
+  input.placeholder = placeholder;
+  input.addEventListener("input", () => onChange(input.value));
+  return input;
+}
+
+function button(label: string, onClick: () => void, className = ""): HTMLButtonElement {
+  const btn = document.createElement("button");
+  btn.type = "button";
+  btn.textContent = label;
+  btn.className = className;
+  btn.addEventListener("click", onClick);
+  return btn;
+}
+
+// ─── Promoting auto-discovered categories ───
+// Discovered categories (e.g. Pinned) aren't in config.json. Editing one
+// promotes it to a regular category, saved with the resolved target paths,
+// so the arrangement survives restarts. The backend then skips those targets
+// when discovering, and removed ones are remembered in `discovery.hidden`.
+// Items discovered after an earlier promotion show up as a second category of
+// the same name; promoting that merges it into the saved one, since names
+// must stay unique.
+function promote(category: Category) {
+  if (!category.discovered || !config) return;
+  // Only these turn into saved items; a saved category's `source` items stay
+  // regenerated
+  const promoted = category.shortcuts;
+  const name = category.name.trim().toLowerCase();
+  const saved = config.categories.find(
+    (c) => c !== category && !c.discovered && c.name.trim().toLowerCase() === name
+  );
+  if (saved) {
+    const paths = new Set(saved.shortcuts.map((s) => s.path.toLowerCase()));
+    const added = category.shortcuts.filter((s) => !paths.has(s.path.toLowerCase()));
+    // The edited object takes the saved one's place, so the caller's edit
+    // (e.g. a move within `category.shortcuts`) applies to the merged list
+    Object.assign(category, saved, { shortcuts: [...saved.shortcuts, ...added] });
+    config.categories.splice(config.categories.indexOf(category), 1);
+    config.categories.splice(config.categories.indexOf(saved), 1, category);
+    // After the caller's edit: the saved category's rows are gone
+    queueMicrotask(render);
+  }
+  category.discovered = false;
+  for (const shortcut of promoted) shortcut.discovered = false;
+}
+
+function hideDiscovered(path: string) {
+  if (!config) return;
+  config.discovery ??= {};
+  config.discovery.hidden ??= [];
+  if (!config.discovery.hidden.includes(path)) config.discovery.hidden.push(path);
+}
+
+function moveShortcut(category: Category, shortcut: Shortcut, offset: number) {
+  // Promoting first: a merge can change where the shortcut sits
+  promote(category);
+  const from = category.shortcuts.indexOf(shortcut);
+  const to = from + offset;
+  // Merged away as a duplicate of a saved item
+  if (from < 0 || to < 0 || to >= category.shortcuts.length) return;
+  category.shortcuts.splice(from, 1);
+  category.shortcuts.splice(to, 0, shortcut);
+  render();
+}
+
+// ─── Rendering ───
+function renderCategory(category: Category): HTMLElement {
+  const el = document.createElement("div");
+  el.className = "category";
+  el.classList.toggle("discovered", !!category.discovered);
+
+  const header = document.createElement("div");
+  header.className = "category-header";
+  header.append(
+    textInput("category-name", category.name, "Category name", (v) => {
+      promote(category);
+      category.name = v;
+    }),
+    button("Add shortcut", () => {
+      promote(category);
+      category.shortcuts.push({ name: "", path: "" });
+      render();
+    }),
+    button("✕", () => {
+      if (category.discovered) category.shortcuts.forEach((s) => hideDiscovered(s.path));
+      config?.categories.splice(config.categories.indexOf(category), 1);
+      render();
+    }, "remove")
+  );
+  el.appendChild(header);
+
+  if (category.discovered) {
+    const note = document.createElement("p");
+    note.className = "category-note";
+    note.textContent =
+      "Auto-discovered or from config.d. Editing it saves it to the config file.";
+    el.appendChild(note);
+  }
+
+  for (const shortcut of category.shortcuts) {
+    // Items found via `source` are regenerated on load, not edited
+    if (shortcut.discovered && !category.discovered) continue;
+
+    const row = document.createElement("div");
+    row.className = "shortcut-row";
+    row.append(
+      textInput("shortcut-name", shortcut.name, "Name", (v) => {
+        promote(category);
+        shortcut.name = v;
+      }),
+      textInput("shortcut-path", shortcut.path, "C:\\Path\\to\\app.exe", (v) => {
+        promote(category);
+        shortcut.path = v;
+      }),
+      button("↑", () => moveShortcut(category, shortcut, -1), "move"),
+      button("↓", () => moveShortcut(category, shortcut, 1), "move"),
+      button("✕", () => {
+        if (category.discovered) hideDiscovered(shortcut.path);
+        promote(category);
+        category.shortcuts.splice(category.shortcuts.indexOf(shortcut), 1);
+        render();
+      }, "remove")
+    );
+    el.appendChild(row);
+  }
+
+  return el;
+}
+
+function render() {
+  if (!config || !categoriesEl) return;
+  categoriesEl.replaceChildren(...config.categories.map(renderCategory));
+}
+
+async function save() {
+  if (!config) return;
+
+  for (const category of config.categories) {
+    category.name = category.name.trim();
+    category.shortcuts = category.shortcuts.filter(
+      (s) => !needsPath(s) || s.path.trim() !== ""
+    );
+    for (const shortcut of category.shortcuts) {
+      shortcut.path = shortcut.path.trim();
+      shortcut.name = shortcut.name.trim() || shortcut.path;
+    }
+  }
+  if (config.categories.some((c) => c.name === "")) {
+    setStatus("Every category needs a name.", true);
+    return;
+  }
+
+  try {
+    await invoke("save_config", { config });
+    setStatus("Saved.");
+    render();
+    showConfigInfo();
+  } catch (err) {
+    setStatus(`Save failed: ${err}`, true);
+  }
+}
+
+// ═══ Bootstrap ═══
+async function bootstrap() {
+  categoriesEl = document.getElementById("categories");
+  statusEl = document.getElementById("status");
+
+  document.getElementById("add-category")?.addEventListener("click", () => {
+    config?.categories.push({ name: "New category", shortcuts: [] });
+    render();
+  });
+  document.getElementById("save")?.addEventListener("click", save);
+
+  showConfigInfo();
+
+  try {
+    // Auto-discovered categories are listed too; see `promote`
+    config = await invoke("get_config");
+    render();
+  } catch (err) {
+    setStatus(`Failed to load config: ${err}`, true);
+  }
+}
+
+window.addEventListener("DOMContentLoaded", () => {
+  bootstrap();
+});